@@ -1,63 +1,3160 @@
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use std::cmp::Ordering;
+
+// Must match the keypair the program is deployed with - after the first `anchor build`, replace it
+// with `solana address -k target/deploy/wall_of_wish-keypair.json` (or run `anchor keys sync`), update
+// Anchor.toml and the frontend's PROGRAM_ID to the same key, then rebuild before deploying
+declare_id!("HZSqkqsgtJkFLwgyFMQHHbFEsU9jPdGZgBTpbrVRwJ8U");
+
+// Layout version written to the first data byte of every new wish. migrate_account upgrades wishes
+// written in an older layout; bump this whenever AWish changes.
+#[constant]
+pub const WISH_VERSION: u8 = 2;
+
+// Byte offsets of the AWish fields indexers filter on with memcmp. Fixed-size fields come before
+// every variable-length one, so these never move when a title or body changes length.
+#[constant]
+pub const WISH_USER_OFFSET: usize = 9;
+#[constant]
+pub const WISH_CATEGORY_OFFSET: usize = 66;
+#[constant]
+pub const WISH_CREATOR_OFFSET: usize = 67;
+#[constant]
+pub const WISH_INDEX_OFFSET: usize = 138;
+#[constant]
+pub const WISH_BOARD_OFFSET: usize = 212;
+
+// Maximum wish title length in characters (tweet-sized) - the limit users see
+#[constant]
+pub const MAX_TITLE_CHARS: usize = 280;
+
+// Maximum wish title size in bytes, which is what the account is sized by. Two bytes per character,
+// so any 280-character title in Latin, Greek, Cyrillic, Hebrew or Arabic script fits; titles of
+// wider characters such as CJK or emoji hit this limit first. A title and body both at their byte
+// limits won't fit in one transaction together.
+#[constant]
+pub const MAX_TITLE_LEN: usize = 560;
+
+// Maximum wish body length in bytes - kept small enough for the submission to fit in one transaction
+#[constant]
+pub const MAX_BODY_LEN: usize = 512;
+
+// Minimum number of seconds between two submissions from the same wallet
+#[constant]
+pub const COOLDOWN_SECS: i64 = 30;
+
+// Maximum comment length in bytes
+#[constant]
+pub const MAX_COMMENT_LEN: usize = 200;
+
+// Maximum off-chain content URI length in bytes - enough for an ipfs:// or ar:// link
+#[constant]
+pub const MAX_CONTENT_URI_LEN: usize = 128;
+
+// Maximum number of wishes delete_all closes in one transaction, to stay well within compute limits
+#[constant]
+pub const MAX_DELETE_BATCH: usize = 10;
+
+// Maximum number of wishes submit_wishes creates in one transaction. Each one derives its PDA,
+// creates an account and may grow the wish index, which together cost several times what a
+// delete does, so this leaves ample headroom under the default 200k compute units.
+#[constant]
+pub const MAX_SUBMIT_BATCH: usize = 8;
+
+// Seconds between request_delete and the earliest confirm_delete (a day)
+#[constant]
+pub const DELETE_GRACE_SECS: i64 = 24 * 60 * 60;
+
+// Price of pinning a wish to the top of the wall, per second
+#[constant]
+pub const PIN_LAMPORTS_PER_SEC: u64 = 1_000;
+
+// Maximum board name length in bytes. The PDA is seeded by the name's hash, so this only bounds the
+// account size.
+#[constant]
+pub const MAX_BOARD_NAME_LEN: usize = 64;
+
+// Slots a wish index grows by when it fills up. It shrinks back once two chunks are free, leaving
+// one spare, so alternating submits and deletes don't resize it every time.
+#[constant]
+pub const WISH_INDEX_CHUNK: usize = 4;
+
+// Maximum number of wishes one wallet's index can list - bounds the heap needed to load it
+#[constant]
+pub const MAX_INDEXED_WISHES: usize = 256;
+
+// Most a single like can count towards a wish's weighted_likes
+#[constant]
+pub const MAX_LIKE_WEIGHT: u64 = 10;
+
+// Profile age that earns a voter one extra unit of like weight (a week)
+#[constant]
+pub const LIKE_WEIGHT_AGE_SECS: i64 = 7 * 24 * 60 * 60;
+
+// Lamports tipped in total that earn a voter one extra unit of like weight (0.1 SOL)
+#[constant]
+pub const LIKE_WEIGHT_TIP_LAMPORTS: u64 = 100_000_000;
+
+// Number of wishers kept on the leaderboard
+#[constant]
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Number of recent actions kept in each user's activity log
+#[constant]
+pub const ACTIVITY_LOG_SIZE: usize = 8;
+
+// `ActivityEntry::kind` values
+#[constant]
+pub const ACTIVITY_SUBMIT: u8 = 0;
+#[constant]
+pub const ACTIVITY_DELETE: u8 = 1;
+#[constant]
+pub const ACTIVITY_LIKE: u8 = 2;
+
+#[program]
+pub mod wall_of_wish {
+    use super::*;
+
+    // Create the singleton stats, leaderboard and treasury accounts for the wall - must run once before
+    // any wish is submitted. Whoever initializes the wall becomes its admin.
+    pub fn initialize_wall(ctx: Context<InitializeWall>) -> Result<()> {
+        // Belt and braces: Anchor's entrypoint already rejects every instruction with
+        // DeclaredProgramIdMismatch (4100) when the program runs under an address other than
+        // `declare_id!`, before any handler is reached, so in practice this never fires
+        require_keys_eq!(*ctx.program_id, crate::ID, ErrorCode::ProgramIdMismatch);
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_submitted = 0;
+        stats.active = 0;
+        stats.admin = ctx.accounts.authority.key();
+        stats.paused = false;
+        stats.featured_wish = None;
+        stats.featured_at = 0;
+        stats.pending_admin = None;
+        stats.fee_lamports = 0;
+        stats.max_wishes_per_user = 0;
+        stats.match_ratio_bps = 0;
+        stats.match_pool_remaining = 0;
+        stats.like_weighting = false;
+        stats.two_step_delete = false;
+        ctx.accounts.leaderboard.entries = Default::default();
+        ctx.accounts.treasury.collected = 0;
+        Ok(())
+    }
+
+    // Open a new board - an independent wall with its own wishes and counters. Anyone can create
+    // one and becomes its admin; names are first come, first served.
+    pub fn create_board(ctx: Context<CreateBoard>, name: String) -> Result<()> {
+        require!(!name.trim().is_empty(), ErrorCode::BoardNameEmpty);
+        require!(name.len() <= MAX_BOARD_NAME_LEN, ErrorCode::BoardNameTooLong);
+
+        let board = &mut ctx.accounts.board;
+        board.admin = ctx.accounts.creator.key();
+        board.total_submitted = 0;
+        board.active = 0;
+        board.name = name;
+        Ok(())
+    }
+
+    // Freeze or resume new submissions - deletes stay allowed so users can always reclaim rent
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        ctx.accounts.stats.paused = paused;
+        Ok(())
+    }
+
+    // Set the fee charged per submitted wish, paid into the treasury. 0 turns the fee off.
+    pub fn set_fee(ctx: Context<SetFee>, fee_lamports: u64) -> Result<()> {
+        ctx.accounts.stats.fee_lamports = fee_lamports;
+        Ok(())
+    }
+
+    // Set how many active wishes one wallet may hold at a time. 0 removes the cap. Lowering it
+    // doesn't touch existing wishes; it only blocks new ones until the wallet is under the cap.
+    pub fn set_wish_cap(ctx: Context<SetWishCap>, max_wishes_per_user: u32) -> Result<()> {
+        ctx.accounts.stats.max_wishes_per_user = max_wishes_per_user;
+        Ok(())
+    }
+
+    // Have the treasury match tips: each tip earns an extra `match_ratio_bps` basis points of itself
+    // from the treasury, until `match_pool` lamports in total have been handed out. Calling this again
+    // replaces both, so it also tops up or ends a matching round.
+    pub fn set_matching(ctx: Context<SetMatching>, match_ratio_bps: u16, match_pool: u64) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        stats.match_ratio_bps = match_ratio_bps;
+        stats.match_pool_remaining = match_pool;
+        Ok(())
+    }
+
+    // Turn weighted likes on or off. While on, each new like adds a weight based on the voter's
+    // profile age and tips given to `weighted_likes`; while off it adds 1. Likes already cast keep
+    // the weight they were cast with, so unliking takes back exactly what was added.
+    pub fn set_like_weighting(ctx: Context<SetLikeWeighting>, enabled: bool) -> Result<()> {
+        ctx.accounts.stats.like_weighting = enabled;
+        Ok(())
+    }
+
+    // Require owners to delete in two steps. While on, delete_wish and delete_all only close wishes
+    // whose deletion was requested at least DELETE_GRACE_SECS ago, just like confirm_delete.
+    pub fn set_two_step_delete(ctx: Context<SetTwoStepDelete>, enabled: bool) -> Result<()> {
+        ctx.accounts.stats.two_step_delete = enabled;
+        Ok(())
+    }
+
+    // Move collected fees out of the treasury to the admin. The treasury is owned by this program, so
+    // its lamports can be moved directly; it always keeps enough to stay rent-exempt.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let reserve = Rent::get()?.minimum_balance(treasury.data_len());
+        require!(
+            treasury.lamports().saturating_sub(reserve) >= amount,
+            ErrorCode::InsufficientTreasury
+        );
+
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.admin.to_account_info().try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    // First step of an admin handoff: the current admin nominates a successor, who must then accept.
+    // Until then the current admin stays in charge, so a mistyped key can simply be re-proposed.
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.stats.pending_admin = Some(new_admin);
+        Ok(())
+    }
+
+    // Second step of an admin handoff: the nominee signs to take over
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        let pending = stats.pending_admin.ok_or(ErrorCode::NoPendingAdmin)?;
+        require_keys_eq!(pending, ctx.accounts.new_admin.key(), ErrorCode::Unauthorized);
+
+        stats.admin = pending;
+        stats.pending_admin = None;
+        Ok(())
+    }
+
+    // Feature one wish on the wall, or clear the featured slot with None. The wish being featured
+    // is passed as the first remaining account so it can be checked to really be a wish.
+    pub fn set_featured(ctx: Context<SetFeatured>, wish: Option<Pubkey>) -> Result<()> {
+        if let Some(wish) = wish {
+            let info = ctx.remaining_accounts.first().ok_or(ErrorCode::NotAWish)?;
+            require_keys_eq!(info.key(), wish, ErrorCode::NotAWish);
+            // Checks the account is owned by this program and carries the AWish discriminator
+            Account::<AWish>::try_from(info).map_err(|_| ErrorCode::NotAWish)?;
+        }
+
+        let stats = &mut ctx.accounts.stats;
+        stats.featured_wish = wish;
+        stats.featured_at = match wish {
+            Some(_) => now()?,
+            None => 0,
+        };
+        Ok(())
+    }
+
+    // Submit a wish (stored in a PDA keyed by a client-chosen wish_id). An anonymous wish is filed
+    // under anonymous_owner instead of the signer - see that function for what it does and doesn't hide.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_wish(
+        ctx: Context<SubmitWish>,
+        wish_id: u64,
+        title: String,
+        body: String,
+        category: Category,
+        visibility: Visibility,
+        ttl_secs: u64,
+        guardian: Option<Pubkey>,
+        content_hash: [u8; 32],
+        content_uri: String,
+        anonymous: bool,
+    ) -> Result<()> {
+        require!(!ctx.accounts.stats.paused, ErrorCode::WallPaused);
+        validate_title(&title)?;
+        validate_body(&body)?;
+        validate_content_uri(&content_uri)?;
+        // Checked before the cooldown so a reused id is reported as such
+        require!(ctx.accounts.wish.owner != ctx.program_id, ErrorCode::DuplicateWish);
+        // Reaping has no signer to find the submitter's profile through, so anonymous wishes never expire
+        require!(!anonymous || ttl_secs == 0, ErrorCode::AnonymousExpiry);
+        let owner = submitter_key(&ctx.accounts.board.key(), ctx.accounts.user.key, wish_id, anonymous);
+        // An index that already names its key was parked by admin_remove_anonymous_wish and not yet
+        // claimed. It's taken over by this wish: the removed one comes off the active count now, and
+        // the parked rent goes back along with this wish's when it is deleted.
+        if anonymous && ctx.accounts.wish_index.user == owner {
+            let profile = &mut ctx.accounts.profile;
+            profile.active_count = profile.active_count.saturating_sub(1);
+        }
+
+        let now = now()?;
+        let index = record_submissions(
+            &mut ctx.accounts.profile,
+            &mut ctx.accounts.stats,
+            &mut ctx.accounts.board,
+            1,
+            now,
+        )?;
+        pay_treasury(
+            &ctx.accounts.user.to_account_info(),
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.stats.fee_lamports,
+        )?;
+        // An anonymous wish still counts on the signer's profile, for the cooldown and the cap, but
+        // it stays off the leaderboard and the activity log, which would list it under the wallet
+        if !anonymous {
+            update_leaderboard(
+                &mut ctx.accounts.leaderboard,
+                ctx.accounts.user.key(),
+                ctx.accounts.profile.wish_count,
+                ctx.accounts.stats.total_submitted - 1,
+            );
+        }
+
+        let entry = NewWish {
+            wish_id,
+            title,
+            body,
+            category,
+            visibility,
+            ttl_secs,
+            guardian,
+            content_hash,
+            content_uri,
+        };
+        let mut wish = AWish::default();
+        init_wish(&mut wish, owner, ctx.accounts.board.key(), index, entry, now);
+        if anonymous {
+            wish.user = Pubkey::default(); // The creator keeps the anonymous key, which the seeds need
+        } else {
+            log_activity(&mut ctx.accounts.profile, ACTIVITY_SUBMIT, now, ctx.accounts.wish.key());
+        }
+        index_insert(
+            &mut ctx.accounts.wish_index,
+            owner,
+            ctx.accounts.wish.key(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        create_wish(
+            &ctx.accounts.wish,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            *ctx.bumps.get("wish").unwrap(),
+            &wish,
+        )?;
+
+        emit!(WishSubmitted {
+            user: wish.user,
+            title: wish.title,
+            wish: ctx.accounts.wish.key(),
+        });
+        Ok(())
+    }
+
+    // Submit several wishes in one transaction. The wish PDAs are passed through remaining_accounts,
+    // one per entry and in the same order, and are created here rather than by an init constraint.
+    pub fn submit_wishes<'info>(
+        ctx: Context<'_, '_, '_, 'info, SubmitWishes<'info>>,
+        entries: Vec<NewWish>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.stats.paused, ErrorCode::WallPaused);
+        // Checked up front, so an oversized batch fails cleanly instead of running out of compute
+        require!(
+            entries.len() <= MAX_SUBMIT_BATCH && ctx.remaining_accounts.len() <= MAX_SUBMIT_BATCH,
+            ErrorCode::BatchTooLarge
+        );
+        require!(ctx.remaining_accounts.len() == entries.len(), ErrorCode::AccountMismatch);
+
+        let now = now()?;
+        let first_index = record_submissions(
+            &mut ctx.accounts.profile,
+            &mut ctx.accounts.stats,
+            &mut ctx.accounts.board,
+            entries.len(),
+            now,
+        )?;
+        pay_treasury(
+            &ctx.accounts.user.to_account_info(),
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.stats.fee_lamports * entries.len() as u64,
+        )?;
+        update_leaderboard(
+            &mut ctx.accounts.leaderboard,
+            ctx.accounts.user.key(),
+            ctx.accounts.profile.wish_count,
+            ctx.accounts.stats.total_submitted - 1,
+        );
+
+        let user = ctx.accounts.user.to_account_info();
+        let board = ctx.accounts.board.key();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        for (i, (entry, wish_info)) in entries.iter().zip(ctx.remaining_accounts).enumerate() {
+            validate_title(&entry.title)?;
+            validate_body(&entry.body)?;
+            validate_content_uri(&entry.content_uri)?;
+            // Two entries with the same id would target the same PDA
+            require!(
+                entries[..i].iter().all(|other| other.wish_id != entry.wish_id),
+                ErrorCode::DuplicateWishInBatch
+            );
+
+            let wish_id_bytes = entry.wish_id.to_le_bytes();
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"wish", board.as_ref(), user.key.as_ref(), &wish_id_bytes],
+                ctx.program_id,
+            );
+            require_keys_eq!(wish_info.key(), address, ErrorCode::WishAddressMismatch);
+            require!(wish_info.owner != ctx.program_id, ErrorCode::DuplicateWish);
+
+            let mut wish = AWish::default();
+            init_wish(&mut wish, user.key(), board, first_index + i as u64, entry.clone(), now);
+            log_activity(&mut ctx.accounts.profile, ACTIVITY_SUBMIT, now, address);
+            index_insert(&mut ctx.accounts.wish_index, user.key(), address, &user, &system_program)?;
+            create_wish(wish_info, &user, &system_program, ctx.program_id, bump, &wish)?;
+
+            emit!(WishSubmitted {
+                user: wish.user,
+                title: wish.title,
+                wish: address,
+            });
+        }
+        Ok(())
+    }
+
+    // First half of a hidden submission, e.g. for contests: reserve the wish PDA with only a hash of
+    // the title, `hash(title || nonce)`. The placeholder isn't an AWish, so it can't be liked,
+    // tipped or listed until it is revealed.
+    pub fn commit_wish(ctx: Context<CommitWish>, wish_id: u64, commitment: [u8; 32]) -> Result<()> {
+        require!(!ctx.accounts.stats.paused, ErrorCode::WallPaused);
+
+        let committed = &mut ctx.accounts.wish;
+        committed.user = ctx.accounts.user.key();
+        committed.board = ctx.accounts.board.key();
+        committed.wish_id = wish_id;
+        committed.commitment = commitment;
+        committed.committed_at = now()?;
+        Ok(())
+    }
+
+    // Second half of a hidden submission: publish the title and nonce behind a commitment. If they
+    // hash to it, the placeholder is grown into a full wish holding the title and counted as a
+    // submission from this point on, cooldown and fee included.
+    pub fn reveal_wish(ctx: Context<RevealWish>, _wish_id: u64, title: String, nonce: [u8; 32]) -> Result<()> {
+        require!(!ctx.accounts.stats.paused, ErrorCode::WallPaused);
+        let wish_info = ctx.accounts.wish.to_account_info();
+        // Checks the account is owned by this program and carries the WishCommitment discriminator
+        let committed = Account::<WishCommitment>::try_from(&wish_info).map_err(|_| ErrorCode::NotCommitted)?;
+        require!(
+            hashv(&[title.as_bytes(), &nonce]).to_bytes() == committed.commitment,
+            ErrorCode::CommitmentMismatch
+        );
+        validate_title(&title)?;
+
+        let now = now()?;
+        let index = record_submissions(
+            &mut ctx.accounts.profile,
+            &mut ctx.accounts.stats,
+            &mut ctx.accounts.board,
+            1,
+            now,
+        )?;
+        pay_treasury(
+            &ctx.accounts.user.to_account_info(),
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.stats.fee_lamports,
+        )?;
+        update_leaderboard(
+            &mut ctx.accounts.leaderboard,
+            ctx.accounts.user.key(),
+            ctx.accounts.profile.wish_count,
+            ctx.accounts.stats.total_submitted - 1,
+        );
+
+        let entry = NewWish {
+            wish_id: committed.wish_id,
+            title,
+            body: String::new(),
+            category: Category::default(),
+            visibility: Visibility::default(),
+            ttl_secs: 0,
+            guardian: None,
+            content_hash: [0; 32],
+            content_uri: String::new(),
+        };
+        let mut wish = AWish::default();
+        init_wish(&mut wish, committed.user, committed.board, index, entry, now);
+        log_activity(&mut ctx.accounts.profile, ACTIVITY_SUBMIT, now, wish_info.key());
+        index_insert(
+            &mut ctx.accounts.wish_index,
+            committed.user,
+            wish_info.key(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        // Grow the placeholder to fit the wish, with the user topping up the rent
+        let space = account_size(wish.title.len(), wish.body.len(), wish.content_uri.len());
+        let top_up = Rent::get()?.minimum_balance(space).saturating_sub(wish_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: wish_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        wish_info.realloc(space, false)?;
+        wish.try_serialize(&mut &mut wish_info.try_borrow_mut_data()?[..])?;
+
+        emit!(WishSubmitted {
+            user: wish.user,
+            title: wish.title,
+            wish: wish_info.key(),
+        });
+        Ok(())
+    }
+
+    // Withdraw a commitment that hasn't been revealed, freeing the wish id and returning the
+    // placeholder's rent to the committer
+    pub fn cancel_commit(_ctx: Context<CancelCommit>, _wish_id: u64) -> Result<()> {
+        Ok(())
+    }
+
+    // Edit a wish's title and body in place - the PDA doesn't depend on either, so it stays put.
+    // Pass the current title to edit only the body.
+    pub fn update_wish(
+        ctx: Context<UpdateWish>,
+        _wish_id: u64,
+        new_title: String,
+        new_body: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.wish.sealed, ErrorCode::WishSealed);
+        validate_title(&new_title)?;
+        validate_body(&new_body)?;
+
+        // The realloc constraint has already resized the account to fit the new title and body
+        let wish = &mut ctx.accounts.wish;
+        wish.title = new_title;
+        wish.body = new_body;
+        wish.last_edited_at = now()?;
+        Ok(())
+    }
+    
+    // Point a wish at its off-chain content. The hash lets clients check the blob behind the URI
+    // hasn't changed; the account is resized to fit the new URI.
+    pub fn update_content(
+        ctx: Context<UpdateContent>,
+        _wish_id: u64,
+        content_hash: [u8; 32],
+        content_uri: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.wish.sealed, ErrorCode::WishSealed);
+        validate_content_uri(&content_uri)?;
+
+        let wish = &mut ctx.accounts.wish;
+        wish.content_hash = content_hash;
+        wish.content_uri = content_uri;
+        Ok(())
+    }
+
+    // Pay to pin a wish to the top of the wall for `duration_secs`. The fee goes to the treasury.
+    // Pinning a wish that's still pinned extends the current pin rather than starting over.
+    pub fn pin_wish(ctx: Context<PinWish>, _wish_id: u64, duration_secs: u64) -> Result<()> {
+        require!(duration_secs > 0, ErrorCode::ZeroPinDuration);
+        let fee = duration_secs.checked_mul(PIN_LAMPORTS_PER_SEC).ok_or(ErrorCode::PinTooLong)?;
+        let duration = i64::try_from(duration_secs).map_err(|_| ErrorCode::PinTooLong)?;
+
+        pay_treasury(
+            &ctx.accounts.user.to_account_info(),
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.system_program.to_account_info(),
+            fee,
+        )?;
+
+        let now = now()?;
+        let wish = &mut ctx.accounts.wish;
+        wish.pinned_until = wish.pinned_until.max(now).checked_add(duration).ok_or(ErrorCode::PinTooLong)?;
+        Ok(())
+    }
+
+    // Mark a wish as granted - only the creator can do this, and only once
+    pub fn mark_fulfilled(ctx: Context<MarkFulfilled>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        require!(!wish.fulfilled, ErrorCode::AlreadyFulfilled);
+        wish.fulfilled = true;
+        wish.granted_by = wish.claimed_by; // Credit whoever had claimed it, if anyone
+        Ok(())
+    }
+
+    // Volunteer to make a wish happen. Only one claimer at a time; they or the owner can unclaim.
+    pub fn claim_wish(ctx: Context<ClaimWish>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        require!(!wish.fulfilled, ErrorCode::AlreadyFulfilled);
+        require!(wish.claimed_by.is_none(), ErrorCode::AlreadyClaimed);
+
+        wish.claimed_by = Some(ctx.accounts.claimer.key());
+        wish.claimed_at = now()?;
+        Ok(())
+    }
+
+    // Release a claim so someone else can take the wish on
+    pub fn unclaim_wish(ctx: Context<UnclaimWish>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        let claimer = wish.claimed_by.ok_or(ErrorCode::NotClaimed)?;
+        let signer = ctx.accounts.signer.key();
+        require!(signer == claimer || signer == wish.user, ErrorCode::UnauthorizedUnclaim);
+
+        wish.claimed_by = None;
+        wish.claimed_at = 0;
+        Ok(())
+    }
+
+    // Show or hide a wish on the public wall - this is only a display hint, see Visibility
+    pub fn set_visibility(ctx: Context<SetVisibility>, _wish_id: u64, visibility: Visibility) -> Result<()> {
+        ctx.accounts.wish.visibility = visibility;
+        Ok(())
+    }
+
+    // Read-only check that `wish` is a real wish owned by `expected_owner`. It writes nothing, so other
+    // programs can CPI into it (e.g. to gate an airdrop to wish creators) and just propagate the error.
+    pub fn verify_wish(ctx: Context<VerifyWish>, _wish_id: u64, expected_owner: Pubkey) -> Result<()> {
+        require_keys_eq!(ctx.accounts.wish.user, expected_owner, ErrorCode::WishOwnerMismatch);
+        Ok(())
+    }
+
+    // Permanently lock a wish as a public commitment: once sealed, its owner can no longer edit,
+    // archive or delete it. There is no unseal. Admin removal still works so abuse can be taken
+    // down, and a wish sealed with a ttl still expires as it always would have.
+    pub fn seal_wish(ctx: Context<SealWish>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        require!(!wish.sealed, ErrorCode::AlreadySealed);
+        wish.sealed = true;
+        Ok(())
+    }
+
+    // Hide a wish without closing it. Its likes, tips and comments stay on-chain, but it no longer
+    // counts towards the wall's active total and frontends leave it off the wall.
+    pub fn archive_wish(ctx: Context<ArchiveWish>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        require!(!wish.sealed, ErrorCode::WishSealed);
+        require!(!wish.archived, ErrorCode::AlreadyArchived);
+        remove_active(&mut ctx.accounts.stats, &mut ctx.accounts.board, &mut ctx.accounts.profile, wish);
+        wish.archived = true;
+        Ok(())
+    }
+
+    // Put an archived wish back on the wall
+    pub fn unarchive_wish(ctx: Context<ArchiveWish>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        require!(wish.archived, ErrorCode::NotArchived);
+        wish.archived = false;
+        add_active(&mut ctx.accounts.stats, &mut ctx.accounts.board, &mut ctx.accounts.profile, 1)
+    }
+
+    // Hand a wish over to another wallet. The PDA is seeded by the immutable creator key rather
+    // than the current owner, so the address (and every like/comment PDA seeded by it) stays put.
+    // The wish moves to the new owner's active count, so it can't push them over the per-user cap.
+    pub fn transfer_wish(ctx: Context<TransferWish>, _wish_id: u64, new_owner: Pubkey) -> Result<()> {
+        // A zeroed owner marks a wish as anonymous
+        require_keys_neq!(new_owner, Pubkey::default(), ErrorCode::InvalidNewOwner);
+        // A transfer to yourself passes the same profile and index twice; leave them alone
+        if new_owner != ctx.accounts.current_owner.key() {
+            if !ctx.accounts.wish.archived {
+                let to_profile = &mut ctx.accounts.to_profile;
+                check_wish_cap(&ctx.accounts.stats, to_profile.active_count + 1)?;
+                to_profile.active_count += 1;
+                let from_profile = &mut ctx.accounts.from_profile;
+                from_profile.active_count = from_profile.active_count.saturating_sub(1);
+            }
+
+            let wish = ctx.accounts.wish.key();
+            let current_owner = ctx.accounts.current_owner.to_account_info();
+            index_remove(&mut ctx.accounts.from_index, wish, &current_owner)?;
+            index_insert(
+                &mut ctx.accounts.to_index,
+                new_owner,
+                wish,
+                &current_owner,
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+        }
+        ctx.accounts.wish.user = new_owner;
+        Ok(())
+    }
+
+    // Send lamports to the wish owner as a show of support. The owner may be a wallet or a PDA of
+    // another program; either can receive the tip and the match.
+    pub fn tip_wish(ctx: Context<TipWish>, _wish_id: u64, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroTip);
+
+        let owner = ctx.accounts.owner.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        pay_lamports(&ctx.accounts.tipper.to_account_info(), &owner, &system_program, ctx.program_id, amount)?;
+
+        let wish = &mut ctx.accounts.wish;
+        wish.total_tipped += amount;
+
+        let profile = &mut ctx.accounts.profile;
+        mark_seen(profile, now()?);
+        // Feeds like_weight. Owners can't tip their own wishes (SelfTip), so this only counts lamports
+        // that actually left the tipper for someone else's wish.
+        profile.tips_given += amount;
+
+        // Top the tip up from the treasury while the matching pool lasts. The treasury always keeps
+        // its rent reserve.
+        let stats = &mut ctx.accounts.stats;
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let available = treasury
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(treasury.data_len()));
+        let matched = (amount as u128 * stats.match_ratio_bps as u128 / 10_000)
+            .min(stats.match_pool_remaining as u128)
+            .min(available as u128) as u64;
+        if matched > 0 {
+            pay_lamports(&treasury, &owner, &system_program, ctx.program_id, matched)?;
+            stats.match_pool_remaining -= matched;
+        }
+        Ok(())
+    }
+
+    // Leave a message of encouragement on a wish - each comment gets its own PDA indexed by the wish's comment count
+    pub fn add_comment(ctx: Context<AddComment>, _wish_id: u64, text: String) -> Result<()> {
+        require!(!text.is_empty(), ErrorCode::CommentEmpty);
+        require!(text.len() <= MAX_COMMENT_LEN, ErrorCode::CommentTooLong);
+
+        let wish = &mut ctx.accounts.wish;
+        let comment = &mut ctx.accounts.comment;
+        comment.wish = wish.key();
+        comment.author = ctx.accounts.author.key();
+        comment.index = wish.comment_count;
+        comment.created_at = now()?;
+        comment.text = text;
+
+        // Never decremented, so comment indices (and PDAs) are never reused
+        wish.comment_count += 1;
+        Ok(())
+    }
+
+    // Remove a comment - either its author or the wish owner can do this, and the rent always goes back to the author
+    pub fn delete_comment(_ctx: Context<DeleteComment>) -> Result<()> {
+        // The close constraint will handle account closing
+        Ok(())
+    }
+
+    // Flag a wish as abusive - the report PDA can only be created once per reporter, so nobody can spam the counter
+    pub fn report_wish(ctx: Context<ReportWish>, _wish_id: u64) -> Result<()> {
+        let report = &mut ctx.accounts.report;
+        // An initialized report means this reporter already flagged the wish
+        require!(report.reporter == Pubkey::default(), ErrorCode::AlreadyReported);
+        report.wish = ctx.accounts.wish.key();
+        report.reporter = ctx.accounts.reporter.key();
+
+        let wish = &mut ctx.accounts.wish;
+        wish.reports = wish.reports.saturating_add(1);
+        Ok(())
+    }
+
+    // Close a wish whose expiry has passed. Anyone can call this to keep the wall fresh; the rent goes
+    // back to the wish owner and the caller receives no bounty, so there is nothing to gain from
+    // reaping other than a tidier wall.
+    pub fn reap_expired(ctx: Context<ReapExpired>, _wish_id: u64) -> Result<()> {
+        let wish = &ctx.accounts.wish;
+        let now = now()?;
+        require!(wish.expires_at != 0 && now >= wish.expires_at, ErrorCode::NotExpired);
+
+        remove_active(&mut ctx.accounts.stats, &mut ctx.accounts.board, &mut ctx.accounts.profile, wish);
+        index_remove(&mut ctx.accounts.wish_index, wish.key(), &ctx.accounts.owner.to_account_info())?;
+
+        emit!(WishDeleted {
+            user: wish.user,
+            wish: wish.key(),
+        });
+
+        close_wish(&ctx.accounts.wish, ctx.accounts.owner.to_account_info())
+    }
+
+    // Moderator removal - the wall admin or the wish's board admin closes the wish, but the rent still
+    // goes back to the wish owner
+    pub fn admin_remove_wish(ctx: Context<AdminRemoveWish>, _wish_id: u64) -> Result<()> {
+        remove_active(
+            &mut ctx.accounts.stats,
+            &mut ctx.accounts.board,
+            &mut ctx.accounts.profile,
+            &ctx.accounts.wish,
+        );
+        index_remove(
+            &mut ctx.accounts.wish_index,
+            ctx.accounts.wish.key(),
+            &ctx.accounts.owner.to_account_info(),
+        )?;
+
+        emit!(WishDeleted {
+            user: ctx.accounts.wish.user,
+            wish: ctx.accounts.wish.key(),
+        });
+
+        close_wish(&ctx.accounts.wish, ctx.accounts.owner.to_account_info())
+    }
+
+    // Moderator removal of an anonymous wish. Nobody but the submitter can tell whose it is, so the
+    // rent is parked in the wish's one-wish index, and it stays on the submitter's active count,
+    // until they take both back with claim_anonymous_rent.
+    pub fn admin_remove_anonymous_wish(ctx: Context<AdminRemoveAnonymousWish>, _wish_id: u64) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        let board = &mut ctx.accounts.board;
+        stats.active = stats.active.saturating_sub(1);
+        board.active = board.active.saturating_sub(1);
+        let index_info = ctx.accounts.wish_index.to_account_info();
+        index_remove(&mut ctx.accounts.wish_index, ctx.accounts.wish.key(), &index_info)?;
+
+        emit!(WishDeleted {
+            user: Pubkey::default(),
+            wish: ctx.accounts.wish.key(),
+        });
+
+        close_wish(&ctx.accounts.wish, index_info)
+    }
+
+    // Collect the rent of an anonymous wish an admin removed, and take it off the active count. The
+    // index is found through the signer's anonymous key, so only the submitter can claim it.
+    pub fn claim_anonymous_rent(ctx: Context<ClaimAnonymousRent>, _board: Pubkey, _wish_id: u64) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.active_count = profile.active_count.saturating_sub(1);
+        Ok(())
+    }
+
+    // Kill switch for spam: the wall admin closes up to MAX_DELETE_BATCH of one wallet's wishes on a
+    // board at once, passed in remaining_accounts. As with admin_remove_wish the rent goes back to
+    // the wallet, not the admin, and escrowed sponsorships stay refundable. A wish owned by anyone
+    // else aborts the whole transaction.
+    pub fn admin_reap_user<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdminReapUser<'info>>,
+        target: Pubkey,
+    ) -> Result<()> {
+        require!(ctx.remaining_accounts.len() <= MAX_DELETE_BATCH, ErrorCode::BatchTooLarge);
+
+        let owner = ctx.accounts.owner.to_account_info();
+        for wish_info in ctx.remaining_accounts {
+            // Checks the account is owned by this program and carries the AWish discriminator
+            let wish = Account::<AWish>::try_from(wish_info)?;
+            require_keys_eq!(wish.user, target, ErrorCode::WishOwnerMismatch);
+            require_keys_eq!(wish.board, ctx.accounts.board.key(), ErrorCode::BoardMismatch);
+
+            remove_active(&mut ctx.accounts.stats, &mut ctx.accounts.board, &mut ctx.accounts.profile, &wish);
+            index_remove(&mut ctx.accounts.wish_index, wish.key(), &owner)?;
+            emit!(WishDeleted {
+                user: target,
+                wish: wish.key(),
+            });
+            close_wish(&wish, owner.clone())?;
+        }
+        Ok(())
+    }
+
+    // Like a wish - the like PDA can only be created once per voter, so each user likes a wish at most once
+    pub fn like_wish(ctx: Context<LikeWish>) -> Result<()> {
+        let like = &mut ctx.accounts.like;
+        // An initialized like means this voter already liked the wish - unless it was left behind by
+        // an earlier wish at the same address, which is overwritten as if it were fresh
+        require!(
+            like.voter == Pubkey::default() || like.wish_created_at != ctx.accounts.wish.created_at,
+            ErrorCode::AlreadyLiked
+        );
+        like.wish = ctx.accounts.wish.key();
+        like.voter = ctx.accounts.voter.key();
+        like.wish_created_at = ctx.accounts.wish.created_at;
+
+        let now = now()?;
+        let profile = &mut ctx.accounts.profile;
+        mark_seen(profile, now);
+        like.weight = if ctx.accounts.stats.like_weighting { like_weight(profile, now) } else { 1 };
+
+        let wish = &mut ctx.accounts.wish;
+        wish.likes += 1;
+        wish.weighted_likes += like.weight;
+        log_activity(profile, ACTIVITY_LIKE, now, wish.key());
+        Ok(())
+    }
+
+    // Remove a like - the close constraint refunds the like PDA's rent to the voter. A like left
+    // behind by an earlier wish at the same address is closed without touching the current wish.
+    pub fn unlike_wish(ctx: Context<UnlikeWish>) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        if ctx.accounts.like.wish_created_at == wish.created_at {
+            wish.likes = wish.likes.saturating_sub(1);
+            wish.weighted_likes = wish.weighted_likes.saturating_sub(ctx.accounts.like.weight);
+        }
+        Ok(())
+    }
+
+    // Follow a wish - a lasting subscription, separate from likes. One follow PDA per follower per wish.
+    pub fn follow_wish(ctx: Context<FollowWish>, _wish_id: u64) -> Result<()> {
+        let follow = &mut ctx.accounts.follow;
+        // An initialized follow means this user already follows the wish, unless it belongs to an
+        // earlier wish at the same address
+        require!(
+            follow.follower == Pubkey::default() || follow.wish_created_at != ctx.accounts.wish.created_at,
+            ErrorCode::AlreadyFollowing
+        );
+        follow.wish = ctx.accounts.wish.key();
+        follow.follower = ctx.accounts.follower.key();
+        follow.wish_created_at = ctx.accounts.wish.created_at;
+
+        let wish = &mut ctx.accounts.wish;
+        wish.followers += 1;
+        Ok(())
+    }
+
+    // Stop following a wish - the close constraint refunds the follow PDA's rent to the follower. A
+    // follow of an earlier wish at the same address doesn't count against the current one.
+    pub fn unfollow_wish(ctx: Context<UnfollowWish>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        if ctx.accounts.follow.wish_created_at == wish.created_at {
+            wish.followers = wish.followers.saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    // React to a wish with one of a few emoji. Each user holds one reaction per wish; reacting again
+    // switches it, moving the user's count from the old reaction to the new one. The reaction PDA is
+    // reused on a switch, so only the first reaction pays rent.
+    pub fn react_wish(ctx: Context<ReactWish>, _wish_id: u64, reaction: Reaction) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        let record = &mut ctx.accounts.reaction;
+
+        // A freshly created record is all zeroes, so an unset reactor means this is the first reaction.
+        // A record left by an earlier wish at the same address starts over too; its reaction was
+        // never counted on this wish.
+        if record.reactor == Pubkey::default() || record.wish_created_at != wish.created_at {
+            record.wish = wish.key();
+            record.reactor = ctx.accounts.reactor.key();
+            record.wish_created_at = wish.created_at;
+        } else {
+            let old = &mut wish.reactions[record.reaction as usize];
+            *old = old.saturating_sub(1);
+        }
+
+        record.reaction = reaction;
+        wish.reactions[reaction as usize] += 1;
+        Ok(())
+    }
+
+    // Promise lamports toward a wish. They sit in a per-wish escrow PDA until the owner marks the
+    // wish fulfilled and releases them, and each sponsor's total is tracked in its own PDA so it
+    // can be refunded if the wish goes away unfulfilled.
+    pub fn sponsor_wish(ctx: Context<SponsorWish>, _wish_id: u64, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroSponsorship);
+        let wish = &ctx.accounts.wish;
+        require!(!wish.fulfilled, ErrorCode::AlreadyFulfilled);
+
+        // A fresh escrow is bound to this wish. Stamping created_at stops a wish re-submitted at the
+        // same address from inheriting the previous wish's escrow.
+        let escrow = &mut ctx.accounts.escrow;
+        if escrow.sponsors == 0 {
+            escrow.wish = wish.key();
+            escrow.wish_created_at = wish.created_at;
+        }
+        require!(escrow.wish_created_at == wish.created_at, ErrorCode::EscrowMismatch);
+
+        // Released sponsorships stay behind as receipts. One left by an earlier wish at this address
+        // was already paid out, so it starts over as a new contribution to this wish.
+        let sponsorship = &mut ctx.accounts.sponsorship;
+        if sponsorship.amount == 0 || sponsorship.wish_created_at != wish.created_at {
+            sponsorship.wish = wish.key();
+            sponsorship.sponsor = ctx.accounts.sponsor.key();
+            sponsorship.wish_created_at = wish.created_at;
+            sponsorship.amount = 0;
+            escrow.sponsors += 1;
+        }
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sponsor.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        sponsorship.amount += amount;
+        escrow.total += amount;
+        Ok(())
+    }
+
+    // Pay the escrowed lamports out to the owner of a fulfilled wish. The close constraint sends
+    // the whole escrow balance, rent included; sponsorship PDAs stay behind as receipts.
+    pub fn release_escrow(ctx: Context<ReleaseEscrow>, _wish_id: u64) -> Result<()> {
+        require!(ctx.accounts.wish.fulfilled, ErrorCode::WishNotFulfilled);
+        require!(
+            ctx.accounts.escrow.wish_created_at == ctx.accounts.wish.created_at,
+            ErrorCode::EscrowMismatch
+        );
+        Ok(())
+    }
+
+    // Return a sponsor's contribution, at the sponsor's request, once the wish it backed has been
+    // closed (reaped or removed) without its escrow being released. While the wish is up the
+    // lamports stay in escrow until it is fulfilled, and only the owner can hand them back early,
+    // with refund_sponsor. The last refund closes the escrow itself.
+    pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
+        // Once the wish is gone its account is empty, or a different wish lives at the address
+        let wish_info = &ctx.accounts.wish;
+        let closed = wish_info.data_is_empty()
+            || !matches!(
+                AWish::try_deserialize(&mut &wish_info.try_borrow_data()?[..]),
+                Ok(wish) if wish.created_at == ctx.accounts.escrow.wish_created_at
+            );
+        require!(closed, ErrorCode::WishStillExists);
+
+        let amount = ctx.accounts.sponsorship.amount;
+        refund_sponsorship(&mut ctx.accounts.escrow, amount, ctx.accounts.sponsor.to_account_info())
+    }
+
+    // Let the owner of an unfulfilled wish hand a sponsor's contribution back, for instance to
+    // drain the escrow before deleting a wish they've given up on
+    pub fn refund_sponsor(ctx: Context<RefundSponsor>, _wish_id: u64) -> Result<()> {
+        require!(!ctx.accounts.wish.fulfilled, ErrorCode::AlreadyFulfilled);
+        let amount = ctx.accounts.sponsorship.amount;
+        refund_sponsorship(&mut ctx.accounts.escrow, amount, ctx.accounts.sponsor.to_account_info())
+    }
+
+    // Delete a wish from the blockchain. A wish with a guardian also needs the guardian's signature,
+    // passed as a signer in remaining_accounts, so one key alone can't remove it. A wish with
+    // sponsorships in escrow has to be released or refunded first, so deleting it can't strand
+    // the sponsors' lamports. While the wall requires two-step deletes, this behaves like
+    // confirm_delete.
+    pub fn delete_wish(ctx: Context<DeleteWish>, _wish_id: u64) -> Result<()> {
+        let now = now()?;
+        require!(
+            !ctx.accounts.stats.two_step_delete || delete_ready(&ctx.accounts.wish, now),
+            ErrorCode::DeleteNotReady
+        );
+        close_owned_wish(ctx, now)
+    }
+
+    // First step of a two-step delete: announce that the wish will go, so tippers and sponsors can
+    // see it coming. The wish stays fully usable until confirm_delete, and cancel_delete undoes this.
+    // Requesting again restarts the grace period.
+    pub fn request_delete(ctx: Context<ScheduleDelete>, _wish_id: u64) -> Result<()> {
+        let wish = &mut ctx.accounts.wish;
+        require!(!wish.sealed, ErrorCode::WishSealed);
+        wish.delete_requested_at = now()?;
+
+        emit!(DeleteRequested {
+            user: wish.user,
+            wish: wish.key(),
+            deletable_at: wish.delete_requested_at + DELETE_GRACE_SECS,
+        });
+        Ok(())
+    }
+
+    // Withdraw a delete request - the wish stays
+    pub fn cancel_delete(ctx: Context<ScheduleDelete>, _wish_id: u64) -> Result<()> {
+        ctx.accounts.wish.delete_requested_at = 0;
+        Ok(())
+    }
+
+    // Second step of a two-step delete: close a wish whose deletion was requested at least
+    // DELETE_GRACE_SECS ago. Otherwise the same as delete_wish, guardian and escrow checks included.
+    pub fn confirm_delete(ctx: Context<DeleteWish>, _wish_id: u64) -> Result<()> {
+        let now = now()?;
+        require!(delete_ready(&ctx.accounts.wish, now), ErrorCode::DeleteNotReady);
+        close_owned_wish(ctx, now)
+    }
+
+    // Delete several of the signer's wishes in one transaction. remaining_accounts holds each wish
+    // followed by its escrow PDA, which may not exist. All of them must be on the passed board, and
+    // if any belongs to someone else or has sponsorships in escrow the whole transaction fails.
+    // Guarded wishes need their guardian and have to go through delete_wish.
+    pub fn delete_all<'info>(ctx: Context<'_, '_, '_, 'info, DeleteAll<'info>>) -> Result<()> {
+        // Checked up front, so an oversized batch fails cleanly instead of running out of compute
+        require!(ctx.remaining_accounts.len() <= 2 * MAX_DELETE_BATCH, ErrorCode::BatchTooLarge);
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(pairs.remainder().is_empty(), ErrorCode::AccountMismatch);
+
+        let now = now()?;
+        let user = ctx.accounts.user.to_account_info();
+        for pair in pairs {
+            let (wish_info, escrow_info) = (&pair[0], &pair[1]);
+            // Checks the account is owned by this program and carries the AWish discriminator
+            let wish = Account::<AWish>::try_from(wish_info)?;
+            require_keys_eq!(wish.user, user.key(), ErrorCode::UnauthorizedDeletion);
+            require_keys_eq!(wish.board, ctx.accounts.board.key(), ErrorCode::BoardMismatch);
+            require!(!wish.sealed, ErrorCode::WishSealed);
+            require!(!ctx.accounts.stats.two_step_delete || delete_ready(&wish, now), ErrorCode::DeleteNotReady);
+            require!(wish.guardian.is_none(), ErrorCode::GuardianSignatureMissing);
+            let (escrow, _) = Pubkey::find_program_address(&[b"escrow", wish.key().as_ref()], ctx.program_id);
+            require_keys_eq!(escrow_info.key(), escrow, ErrorCode::AccountMismatch);
+            require_escrow_drained(escrow_info, &wish, ctx.program_id)?;
+
+            log_activity(&mut ctx.accounts.profile, ACTIVITY_DELETE, now, wish.key());
+            remove_active(&mut ctx.accounts.stats, &mut ctx.accounts.board, &mut ctx.accounts.profile, &wish);
+            index_remove(&mut ctx.accounts.wish_index, wish.key(), &user)?;
+            emit!(WishDeleted {
+                user: user.key(),
+                wish: wish.key(),
+            });
+            close_wish(&wish, user.clone())?;
+        }
+        Ok(())
+    }
+
+    // Upgrade a wish written in an older layout to the current one, in place. Its address and every
+    // field carry over, and fields the old layout didn't have get their defaults - for v1, which
+    // predates versioning, that's just the version byte. The account grows to the new size and the
+    // owner pays the extra rent.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let info = ctx.accounts.wish.to_account_info();
+        let wish = read_old_wish(&info, ctx.program_id)?;
+        require_keys_eq!(wish.user, ctx.accounts.user.key(), ErrorCode::UnauthorizedUpdate);
+        let (address, _) = Pubkey::find_program_address(
+            &[b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish.wish_id.to_le_bytes()],
+            ctx.program_id,
+        );
+        require_keys_eq!(info.key(), address, ErrorCode::WishAddressMismatch);
+
+        let space = account_size(wish.title.len(), wish.body.len(), wish.content_uri.len());
+        let top_up = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.realloc(space, false)?;
+        wish.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    // One-time move of a wish from the first release, which seeded the PDA with the raw title and
+    // stored only the owner and title, to a `wish_id`-seeded account on a board, in the current layout. The
+    // legacy seeds include the signer's key, so only its owner can migrate it. The title carries over;
+    // everything the old layout didn't have starts fresh. The old account is closed and its rent
+    // returned to the owner, who pays for the new one.
+    pub fn migrate_wish(ctx: Context<MigrateWish>, wish_id: u64, title: String) -> Result<()> {
+        let legacy_info = ctx.accounts.legacy_wish.to_account_info();
+        let legacy = read_legacy_wish(&legacy_info, ctx.program_id)?;
+        require!(legacy.title == title, ErrorCode::NotALegacyWish);
+        require!(ctx.accounts.wish.owner != ctx.program_id, ErrorCode::DuplicateWish);
+
+        // Counted like a new submission, but without the cooldown or fee
+        let stats = &mut ctx.accounts.stats;
+        let board = &mut ctx.accounts.board;
+        let index = stats.total_submitted;
+        stats.total_submitted += 1;
+        board.total_submitted += 1;
+        add_active(stats, board, &mut ctx.accounts.profile, 1)?;
+
+        let entry = NewWish {
+            wish_id,
+            title: legacy.title,
+            body: String::new(),
+            category: Category::default(),
+            visibility: Visibility::default(),
+            ttl_secs: 0,
+            guardian: None,
+            content_hash: [0; 32],
+            content_uri: String::new(),
+        };
+        let now = now()?;
+        mark_seen(&mut ctx.accounts.profile, now);
+        let mut wish = AWish::default();
+        init_wish(&mut wish, legacy.user, ctx.accounts.board.key(), index, entry, now);
+        index_insert(
+            &mut ctx.accounts.wish_index,
+            legacy.user,
+            ctx.accounts.wish.key(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        create_wish(
+            &ctx.accounts.wish,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            *ctx.bumps.get("wish").unwrap(),
+            &wish,
+        )?;
+
+        // The legacy account can't be loaded as an Account<AWish>, so close it by hand
+        let user = ctx.accounts.user.to_account_info();
+        **user.try_borrow_mut_lamports()? += legacy_info.lamports();
+        **legacy_info.try_borrow_mut_lamports()? = 0;
+        legacy_info.assign(&System::id());
+        legacy_info.realloc(0, false)?;
+
+        emit!(WishMigrated {
+            user: wish.user,
+            legacy_wish: legacy_info.key(),
+            wish: ctx.accounts.wish.key(),
+        });
+        Ok(())
+    }
+}
+
+// Current on-chain time. Every instruction reads the clock through here, from the Clock sysvar, so
+// tests running under bankrun or solana-program-test can warp it to exercise time-based logic.
+fn now() -> Result<i64> {
+    Ok(Clock::get()?.unix_timestamp)
+}
+
+// Reject blank, oversized and garbled titles. The byte size drives the account size; the character
+// count is the limit users see, counted in Unicode scalar values (`char`s) since grapheme
+// segmentation would mean shipping Unicode tables on-chain. Control characters (newlines, tabs,
+// NUL, ...) have no place in a one-line headline, so they're refused too.
+fn validate_title(title: &str) -> Result<()> {
+    require!(!title.trim().is_empty(), ErrorCode::TitleEmpty);
+    require!(title.len() <= MAX_TITLE_LEN, ErrorCode::TitleTooLong);
+    require!(title.chars().count() <= MAX_TITLE_CHARS, ErrorCode::TitleTooManyChars);
+    require!(!title.chars().any(char::is_control), ErrorCode::InvalidCharacters);
+    Ok(())
+}
+
+// The body is optional, so only its length is checked
+fn validate_body(body: &str) -> Result<()> {
+    require!(body.len() <= MAX_BODY_LEN, ErrorCode::BodyTooLong);
+    Ok(())
+}
+
+fn validate_content_uri(content_uri: &str) -> Result<()> {
+    require!(content_uri.len() <= MAX_CONTENT_URI_LEN, ErrorCode::ContentUriTooLong);
+    Ok(())
+}
+
+// Fill in a freshly created wish
+fn init_wish(wish: &mut AWish, user: Pubkey, board: Pubkey, index: u64, entry: NewWish, now: i64) {
+    wish.version = WISH_VERSION;
+    wish.user = user;
+    wish.creator = user;
+    wish.board = board;
+    wish.wish_id = entry.wish_id;
+    wish.index = index;
+    wish.created_at = now; // On-chain clock, so it can't be backdated
+    wish.fulfilled = false;
+    wish.category = entry.category;
+    wish.visibility = entry.visibility;
+    wish.expires_at = match entry.ttl_secs {
+        0 => 0, // Never expires
+        ttl => now.saturating_add(i64::try_from(ttl).unwrap_or(i64::MAX)),
+    };
+    wish.last_edited_at = 0; // Not edited yet
+    wish.guardian = entry.guardian;
+    wish.title = entry.title;
+    wish.body = entry.body;
+    wish.content_hash = entry.content_hash;
+    wish.content_uri = entry.content_uri;
+}
+
+// Key an anonymous wish is filed under in place of its submitter: a hash of the board, the submitter
+// and the wish id. It is stored as the wish's creator, with `user` zeroed, and seeds the wish PDA and
+// a one-wish index of its own, so neither names the wallet and two anonymous wishes can't be linked
+// through them. The wish is still counted on the submitter's own profile, which is what the cooldown
+// and the per-user cap are enforced on. The key is not a secret, though: the submitting transaction
+// is signed by the wallet, and anyone can hash a candidate wallet to check a guess.
+fn anonymous_owner(board: &Pubkey, user: &Pubkey, wish_id: u64) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[b"anonymous", board.as_ref(), user.as_ref(), &wish_id.to_le_bytes()]).to_bytes())
+}
+
+// Key a submission's wish PDA and index are seeded by
+fn submitter_key(board: &Pubkey, user: &Pubkey, wish_id: u64, anonymous: bool) -> Pubkey {
+    if anonymous {
+        anonymous_owner(board, user, wish_id)
+    } else {
+        *user
+    }
+}
+
+// Key an existing wish's index is seeded by: its owner, or the anonymous key if it has none
+fn owner_key(wish: &AWish) -> Pubkey {
+    if wish.user == Pubkey::default() {
+        wish.creator
+    } else {
+        wish.user
+    }
+}
+
+// Whether `signer` may delete the wish: its owner, or whoever submitted it anonymously
+fn can_delete(wish: &AWish, signer: &Pubkey) -> bool {
+    wish.user == *signer
+        || (wish.user == Pubkey::default() && wish.creator == anonymous_owner(&wish.board, signer, wish.wish_id))
+}
+
+// Enforce the cooldown and count `count` new wishes on the user's profile, the board and the wall.
+// Returns the index of the first new wish across the whole wall; the rest follow consecutively.
+// Transactions that write the stats account are never processed in parallel, so each index is
+// handed out exactly once.
+fn record_submissions(
+    profile: &mut UserProfile,
+    stats: &mut WallStats,
+    board: &mut Board,
+    count: usize,
+    now: i64,
+) -> Result<u64> {
+    require!(now - profile.last_submit >= COOLDOWN_SECS, ErrorCode::SubmitTooSoon);
+    mark_seen(profile, now);
+    profile.last_submit = now;
+    profile.wish_count += count as u32;
+
+    let first_index = stats.total_submitted;
+    stats.total_submitted += count as u64;
+    board.total_submitted += count as u64;
+    add_active(stats, board, profile, count)?;
+    Ok(first_index)
+}
+
+// Put `count` wishes on the wall's, the board's and the owner's active counts, enforcing the
+// per-user cap
+fn add_active(stats: &mut WallStats, board: &mut Board, profile: &mut UserProfile, count: usize) -> Result<()> {
+    let active_count = profile.active_count + count as u32;
+    check_wish_cap(stats, active_count)?;
+    profile.active_count = active_count;
+    stats.active += count as u64;
+    board.active += count as u64;
+    Ok(())
+}
+
+fn check_wish_cap(stats: &WallStats, active_count: u32) -> Result<()> {
+    require!(
+        stats.max_wishes_per_user == 0 || active_count <= stats.max_wishes_per_user,
+        ErrorCode::WishLimitReached
+    );
+    Ok(())
+}
+
+// Take a wish off the wall's, its board's and its owner's active counts. Archived wishes were
+// already taken off when they were archived.
+fn remove_active(stats: &mut WallStats, board: &mut Board, profile: &mut UserProfile, wish: &AWish) {
+    if !wish.archived {
+        stats.active = stats.active.saturating_sub(1);
+        board.active = board.active.saturating_sub(1);
+        profile.active_count = profile.active_count.saturating_sub(1);
+    }
+}
+
+// Record when a wallet first submitted, liked or tipped - profiles can also be created by a
+// transfer or a delete, which don't count
+fn mark_seen(profile: &mut UserProfile, now: i64) {
+    if profile.first_seen == 0 {
+        profile.first_seen = now;
+    }
+}
+
+// Weight of a like from this voter when weighting is on: 1, plus one per LIKE_WEIGHT_AGE_SECS the
+// profile has existed and one per LIKE_WEIGHT_TIP_LAMPORTS tipped, up to MAX_LIKE_WEIGHT. Fresh
+// wallets count the same as an unweighted like.
+fn like_weight(profile: &UserProfile, now: i64) -> u64 {
+    let age = (now - profile.first_seen).max(0) as u64 / LIKE_WEIGHT_AGE_SECS as u64;
+    let tips = profile.tips_given / LIKE_WEIGHT_TIP_LAMPORTS;
+    1u64.saturating_add(age).saturating_add(tips).min(MAX_LIKE_WEIGHT)
+}
+
+// Append an action to the user's activity log, overwriting the oldest entry once the log is full.
+// The next slot is `activity_total % ACTIVITY_LOG_SIZE`, so that slot also holds the oldest entry.
+fn log_activity(profile: &mut UserProfile, kind: u8, timestamp: i64, target: Pubkey) {
+    let slot = (profile.activity_total % ACTIVITY_LOG_SIZE as u64) as usize;
+    profile.activity[slot] = ActivityEntry { kind, timestamp, target };
+    profile.activity_total += 1;
+}
+
+// Add a wish to its owner's index, growing the account by WISH_INDEX_CHUNK slots if it is full
+fn index_insert<'info>(
+    index: &mut Account<'info, WishIndex>,
+    owner: Pubkey,
+    wish: Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(index.wishes.len() < MAX_INDEXED_WISHES, ErrorCode::WishIndexFull);
+    index.user = owner;
+    index.wishes.push(wish);
+
+    let info = index.to_account_info();
+    let capacity = wish_index_capacity(info.data_len());
+    if index.wishes.len() > capacity {
+        let space = wish_index_size(capacity + WISH_INDEX_CHUNK);
+        let top_up = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+        info.realloc(space, false)?;
+    }
+    Ok(())
+}
+
+// Drop a wish from its owner's index. The last entry is swapped into its slot, so the order of the
+// index isn't meaningful. Once two chunks are free the account shrinks to one spare chunk, and the
+// freed rent goes to `destination`.
+fn index_remove<'info>(
+    index: &mut Account<'info, WishIndex>,
+    wish: Pubkey,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    if let Some(slot) = index.wishes.iter().position(|key| *key == wish) {
+        index.wishes.swap_remove(slot);
+    }
+
+    let info = index.to_account_info();
+    let len = index.wishes.len();
+    if wish_index_capacity(info.data_len()) - len >= 2 * WISH_INDEX_CHUNK {
+        let space = wish_index_size(len + WISH_INDEX_CHUNK);
+        info.realloc(space, false)?;
+        // The index is owned by this program, so its lamports can be moved directly
+        let excess = info.lamports() - Rent::get()?.minimum_balance(space);
+        **info.try_borrow_mut_lamports()? -= excess;
+        **destination.try_borrow_mut_lamports()? += excess;
+    }
+    Ok(())
+}
+
+// Transfer `amount` lamports into the treasury, if there is anything to pay
+fn pay_treasury<'info>(
+    from: &AccountInfo<'info>,
+    treasury: &mut Account<'info, Treasury>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: from.clone(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    treasury.collected += amount;
+    Ok(())
+}
+
+// Order two ranked items, each given as (score, index), best first: the higher score wins, and on
+// a tie the lower wall-wide wish index - whoever got there first - does. `Less` means `a` ranks
+// above `b`. Clients sorting by the same two fields reproduce the program's order exactly.
+fn wish_rank(a: (u64, u64), b: (u64, u64)) -> Ordering {
+    b.0.cmp(&a.0).then(a.1.cmp(&b.1))
+}
+
+// Keep the leaderboard sorted by wish_rank of (wish count, index), best first. Counts only ever
+// grow, so the user either keeps their slot or takes over the lowest one, and then only needs to
+// bubble upwards. `index` is that of the submission that brought the user to `wish_count`.
+fn update_leaderboard(board: &mut Leaderboard, user: Pubkey, wish_count: u32, index: u64) {
+    let rank = |entry: &LeaderboardEntry| (entry.wish_count as u64, entry.index);
+    let entry = LeaderboardEntry { user, wish_count, index };
+    let entries = &mut board.entries;
+    let slot = match entries.iter().position(|entry| entry.user == user) {
+        Some(slot) => slot,
+        None if wish_rank(rank(&entry), rank(&entries[LEADERBOARD_SIZE - 1])) == Ordering::Less => {
+            LEADERBOARD_SIZE - 1
+        }
+        None => return, // Doesn't beat the lowest entry
+    };
+    entries[slot] = entry;
+
+    let mut i = slot;
+    while i > 0 && wish_rank(rank(&entries[i]), rank(&entries[i - 1])) == Ordering::Less {
+        entries.swap(i - 1, i);
+        i -= 1;
+    }
+}
+
+// Size of a user profile:
+// 8 (discriminator) + 8 (last submit) + 4 (wish count) + 4 (active count) + 8 (activity total) + ACTIVITY_LOG_SIZE * (1 (kind) + 8 (timestamp) + 32 (target)) + 8 (first seen) + 8 (tips given)
+const PROFILE_SIZE: usize = 8 + 8 + 4 + 4 + 8 + ACTIVITY_LOG_SIZE * (1 + 8 + 32) + 8 + 8;
+
+// Size of a wish index with room for `capacity` wishes:
+// 8 (discriminator) + 32 (user pubkey) + 4 (vec length) + capacity * 32 (wish pubkeys)
+const fn wish_index_size(capacity: usize) -> usize {
+    8 + 32 + 4 + capacity * 32
+}
+
+const fn wish_index_capacity(data_len: usize) -> usize {
+    (data_len - wish_index_size(0)) / 32
+}
+
+// Space for a wish index at init_if_needed - a new one starts with one chunk, an existing one keeps
+// the size it has grown or shrunk to (init_if_needed insists the size matches)
+fn wish_index_space(index: &AccountInfo) -> usize {
+    match index.data_len() {
+        0 => wish_index_size(WISH_INDEX_CHUNK),
+        len => len,
+    }
+}
+
+// Size of a wish account holding strings of the given byte lengths. Every place that sizes a wish goes
+// through here, so creation and the reallocs can't drift apart:
+// 8 (discriminator) + 1 (version) + 32 (user pubkey) + 8 (wish id) + 8 (created_at) + 8 (likes) + 1 (fulfilled) + 1 (category) + 32 (creator pubkey) + 8 (total tipped) + 8 (comment count) + 2 (reports) + 1 (visibility) + 8 (expires_at) + 4 (followers) + 8 (last_edited_at) + 8 (index) + 16 (reaction counts) + 32 (content hash) + 8 (pinned_until) + 8 (claimed_at) + 1 (archived) + 1 (sealed) + 32 (board pubkey) + 8 (weighted likes) + 8 (delete_requested_at) + 33 (optional guardian) + 33 (optional claimer) + 33 (optional granter) + 4 (string length) + title bytes + 4 (string length) + body bytes + 4 (string length) + content URI bytes
+const fn account_size(title_len: usize, body_len: usize, content_uri_len: usize) -> usize {
+    8 + 1 + 32 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 2 + 1 + 8 + 4 + 8 + 8 + 16 + 32 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 33 + 33 + 33 + 4 + title_len + 4 + body_len + 4 + content_uri_len
+}
+
+// Create a wish PDA sized to fit `wish` and write it out. Callers create the account here rather than
+// through an init constraint so they can first reject a wish id that's already taken with a clear
+// DuplicateWish error instead of the system program's generic "already in use".
+// The address is predictable, so anyone can send it lamports first, and create_account refuses an
+// account with a balance. Like Anchor's init, a funded address is instead topped up to rent-exempt,
+// allocated and assigned to this program.
+fn create_wish<'info>(
+    wish_info: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    bump: u8,
+    wish: &AWish,
+) -> Result<()> {
+    let space = account_size(wish.title.len(), wish.body.len(), wish.content_uri.len());
+    let rent = Rent::get()?.minimum_balance(space);
+    let wish_id_bytes = wish.wish_id.to_le_bytes();
+    let seeds: &[&[u8]] = &[b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id_bytes, &[bump]];
+    let signer = &[seeds];
+
+    if wish_info.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: user.clone(),
+                    to: wish_info.clone(),
+                },
+                signer,
+            ),
+            rent,
+            space as u64,
+            program_id,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(wish_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: user.clone(),
+                        to: wish_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: wish_info.clone(),
+                },
+                signer,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: wish_info.clone(),
+                },
+                signer,
+            ),
+            program_id,
+        )?;
+    }
+
+    // Defensive: a miscounted size would otherwise leave a wish that isn't rent-exempt
+    require!(
+        Rent::get()?.is_exempt(wish_info.lamports(), wish_info.data_len()),
+        ErrorCode::NotRentExempt
+    );
+
+    wish.try_serialize(&mut &mut wish_info.try_borrow_mut_data()?[..])
+}
+
+// Parse a wish in the first release's layout: the AWish discriminator followed by just the owner and
+// title. Anything else, including a wish in the current layout, is rejected.
+fn read_legacy_wish(info: &AccountInfo, program_id: &Pubkey) -> Result<LegacyWish> {
+    require_keys_eq!(*info.owner, *program_id, ErrorCode::NotALegacyWish);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == AWish::DISCRIMINATOR,
+        ErrorCode::NotALegacyWish
+    );
+
+    let mut rest = &data[8..];
+    let legacy = LegacyWish::deserialize(&mut rest).map_err(|_| error!(ErrorCode::NotALegacyWish))?;
+    // The legacy account was sized exactly to its title
+    require!(rest.is_empty(), ErrorCode::NotALegacyWish);
+    Ok(legacy)
+}
+
+// Whether an account of `data` is `size` bytes long with nothing but zeroes in `rest`, the bytes a
+// parse didn't use
+fn sized_for(data: &[u8], rest: &[u8], size: usize) -> bool {
+    data.len() == size && rest.iter().all(|byte| *byte == 0)
+}
+
+// Parse a wish written in an older layout, brought up to the current one. Wish accounts are sized by
+// account_size, which reserves the full 33 bytes for each Option<Pubkey> even though None only takes
+// one, so a parse can leave zeroed bytes at the end. A layout is therefore accepted when it parses,
+// the account is exactly the size that layout would give it and whatever is left over is zero. A
+// wish that is already current, or any other account, is rejected.
+fn read_old_wish(info: &AccountInfo, program_id: &Pubkey) -> Result<AWish> {
+    require_keys_eq!(*info.owner, *program_id, ErrorCode::UnknownWishLayout);
+    parse_old_wish(&info.try_borrow_data()?)
+}
+
+fn parse_old_wish(data: &[u8]) -> Result<AWish> {
+    require!(
+        data.len() > 8 && data[..8] == AWish::DISCRIMINATOR,
+        ErrorCode::UnknownWishLayout
+    );
+
+    let mut rest = &data[8..];
+    let current = match AWish::deserialize(&mut rest) {
+        Ok(wish) => {
+            data[8] == WISH_VERSION
+                && sized_for(data, rest, account_size(wish.title.len(), wish.body.len(), wish.content_uri.len()))
+        }
+        Err(_) => false,
+    };
+    require!(!current, ErrorCode::WishUpToDate);
+
+    // v1 predates the version byte, so it is one byte shorter than the current layout
+    let mut rest = &data[8..];
+    let old = WishV1::deserialize(&mut rest).map_err(|_| error!(ErrorCode::UnknownWishLayout))?;
+    require!(
+        sized_for(data, rest, account_size(old.title.len(), old.body.len(), old.content_uri.len()) - 1),
+        ErrorCode::UnknownWishLayout
+    );
+    Ok(AWish {
+        version: WISH_VERSION,
+        user: old.user,
+        wish_id: old.wish_id,
+        created_at: old.created_at,
+        likes: old.likes,
+        fulfilled: old.fulfilled,
+        category: old.category,
+        creator: old.creator,
+        total_tipped: old.total_tipped,
+        comment_count: old.comment_count,
+        reports: old.reports,
+        visibility: old.visibility,
+        expires_at: old.expires_at,
+        followers: old.followers,
+        last_edited_at: old.last_edited_at,
+        index: old.index,
+        reactions: old.reactions,
+        content_hash: old.content_hash,
+        pinned_until: old.pinned_until,
+        claimed_at: old.claimed_at,
+        archived: old.archived,
+        sealed: old.sealed,
+        board: old.board,
+        weighted_likes: old.weighted_likes,
+        delete_requested_at: old.delete_requested_at,
+        guardian: old.guardian,
+        claimed_by: old.claimed_by,
+        granted_by: old.granted_by,
+        title: old.title,
+        body: old.body,
+        content_uri: old.content_uri,
+    })
+}
+
+// Fail if sponsors still have lamports in escrow for this wish. An escrow that doesn't exist, or that
+// was left behind by an earlier wish at the same address, doesn't count.
+fn require_escrow_drained(escrow: &AccountInfo, wish: &AWish, program_id: &Pubkey) -> Result<()> {
+    if escrow.owner == program_id && !escrow.data_is_empty() {
+        let escrow = Escrow::try_deserialize(&mut &escrow.try_borrow_data()?[..])?;
+        require!(
+            escrow.wish_created_at != wish.created_at || escrow.total == 0,
+            ErrorCode::OutstandingFunds
+        );
+    }
+    Ok(())
+}
+
+// Pay a sponsor's `amount` back out of the escrow. The last refund closes the escrow, its rent going
+// to that sponsor.
+fn refund_sponsorship<'info>(escrow: &mut Account<'info, Escrow>, amount: u64, sponsor: AccountInfo<'info>) -> Result<()> {
+    // The escrow is owned by this program, so its lamports can be moved directly
+    **escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **sponsor.try_borrow_mut_lamports()? += amount;
+
+    escrow.total -= amount;
+    escrow.sponsors -= 1;
+    if escrow.sponsors == 0 {
+        escrow.close(sponsor)?;
+    }
+    Ok(())
+}
+
+// Move `amount` lamports from `from` to `to`. An account this program owns is debited directly, since
+// the system program only transfers out of system accounts; anything else (a signing wallet) goes
+// through a system transfer. The recipient is only ever credited, which the runtime allows for any
+// writable account, so payouts reach owners that are PDAs of other programs just like wallets.
+fn pay_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if from.owner == program_id {
+        **from.try_borrow_mut_lamports()? -= amount;
+        **to.try_borrow_mut_lamports()? += amount;
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+// Whether a requested deletion has waited out its grace period
+fn delete_ready(wish: &AWish, now: i64) -> bool {
+    wish.delete_requested_at != 0 && now - wish.delete_requested_at >= DELETE_GRACE_SECS
+}
+
+// Shared by delete_wish and confirm_delete once they've decided the wish may go now
+fn close_owned_wish(ctx: Context<DeleteWish>, now: i64) -> Result<()> {
+    require!(!ctx.accounts.wish.sealed, ErrorCode::WishSealed);
+    require_escrow_drained(&ctx.accounts.escrow, &ctx.accounts.wish, ctx.program_id)?;
+    if let Some(guardian) = ctx.accounts.wish.guardian {
+        require!(
+            ctx.remaining_accounts.iter().any(|acc| acc.key() == guardian && acc.is_signer),
+            ErrorCode::GuardianSignatureMissing
+        );
+    }
+
+    remove_active(
+        &mut ctx.accounts.stats,
+        &mut ctx.accounts.board,
+        &mut ctx.accounts.profile,
+        &ctx.accounts.wish,
+    );
+    let anonymous = ctx.accounts.wish.user == Pubkey::default();
+    if !anonymous {
+        log_activity(&mut ctx.accounts.profile, ACTIVITY_DELETE, now, ctx.accounts.wish.key());
+    }
+    index_remove(
+        &mut ctx.accounts.wish_index,
+        ctx.accounts.wish.key(),
+        &ctx.accounts.user.to_account_info(),
+    )?;
+    // An anonymous wish's index only ever lists that one wish, so it goes with it
+    if anonymous {
+        ctx.accounts.wish_index.close(ctx.accounts.user.to_account_info())?;
+    }
+
+    emit!(WishDeleted {
+        user: ctx.accounts.wish.user, // Still zeroed for an anonymous wish
+        wish: ctx.accounts.wish.key(),
+    });
+
+    // Close explicitly rather than via the close constraint so the result can be checked here
+    close_wish(&ctx.accounts.wish, ctx.accounts.user.to_account_info())
+}
+
+// Close a wish and verify nothing is left behind: every lamport goes to the destination, the data
+// is truncated to zero bytes (so not even the discriminator survives to be revived) and the account
+// is handed back to the system program
+fn close_wish<'info>(wish: &Account<'info, AWish>, destination: AccountInfo<'info>) -> Result<()> {
+    wish.close(destination)?;
+
+    let info = wish.to_account_info();
+    require!(
+        info.lamports() == 0 && info.data_is_empty() && *info.owner == System::id(),
+        ErrorCode::CloseIncomplete
+    );
+    msg!("Closed wish {}", info.key);
+    Ok(())
+}
+
+// Account structure for setting up the wall
+#[derive(Accounts)]
+pub struct InitializeWall<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 8 + 8 + 32 + 1 + 33 + 8 + 33 + 8 + 4 + 2 + 8 + 1 + 1, // 8 (discriminator) + 8 (total submitted) + 8 (active) + 32 (admin pubkey) + 1 (paused) + 33 (optional featured wish) + 8 (featured_at) + 33 (optional pending admin) + 8 (fee) + 4 (per-user cap) + 2 (match ratio) + 8 (match pool) + 1 (like weighting) + 1 (two-step delete)
+        seeds = [b"stats"], // Singleton PDA for the whole wall
+        bump
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LEADERBOARD_SIZE * (32 + 4 + 8), // 8 (discriminator) + LEADERBOARD_SIZE * (32 (user pubkey) + 4 (wish count) + 8 (index))
+        seeds = [b"leaderboard"], // Singleton PDA for the whole wall
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 8, // 8 (discriminator) + 8 (collected)
+        seeds = [b"treasury"], // Singleton PDA holding submission fees
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct WallStats {
+    pub total_submitted: u64,          // Wishes ever submitted
+    pub active: u64,                   // Wishes currently on the wall
+    pub admin: Pubkey,                 // Key allowed to run admin instructions
+    pub paused: bool,                  // When set, new submissions are rejected
+    pub featured_wish: Option<Pubkey>, // Wish the admin is currently featuring, if any
+    pub featured_at: i64,              // Unix timestamp the featured wish was set (0 = none)
+    pub pending_admin: Option<Pubkey>, // Proposed successor who has yet to accept
+    pub fee_lamports: u64,             // Fee charged per submitted wish (0 = free)
+    pub max_wishes_per_user: u32,      // Active wishes one wallet may hold (0 = unlimited)
+    pub match_ratio_bps: u16,          // Treasury match per tip, in basis points of the tip (0 = off)
+    pub match_pool_remaining: u64,     // Lamports the treasury may still hand out as matches
+    pub like_weighting: bool,          // When set, new likes are weighted by the voter's age and tips
+    pub two_step_delete: bool,         // When set, immediate deletes also wait out the grace period
+}
+
+// Account structure for opening a board
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateBoard<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + 32 + 8 + 8 + 4 + name.len(), // 8 (discriminator) + 32 (admin pubkey) + 8 (total submitted) + 8 (active) + 4 (string length) + name bytes
+        seeds = [b"board", hash(name.as_bytes()).as_ref()], // Hashed, since a seed can't be longer than 32 bytes
+        bump
+    )]
+    pub board: Account<'info, Board>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// One wall of wishes. The wall-wide WallStats still count every board together.
+#[account]
+pub struct Board {
+    pub admin: Pubkey,        // Creator of the board, who can also remove wishes from it
+    pub total_submitted: u64, // Wishes ever submitted to the board
+    pub active: u64,          // Wishes currently on the board
+    pub name: String,         // Display name - its hash seeds the PDA
+}
+
+// Holds submission fees until the admin withdraws them
+#[account]
+pub struct Treasury {
+    pub collected: u64, // Lamports paid in over the treasury's lifetime
+}
+
+// Account structure for setting the submission fee
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for withdrawing fees to the admin
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+// Most active wishers, sorted by wish count and then index (see wish_rank) - unused slots have a zero count
+#[account]
+pub struct Leaderboard {
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LeaderboardEntry {
+    pub user: Pubkey,    // Wisher
+    pub wish_count: u32, // Their UserProfile wish count
+    pub index: u64,      // Wall-wide index of the wish that brought them to wish_count - breaks ties, lower first
+}
+
+// Account structure for configuring tip matching
+#[derive(Accounts)]
+pub struct SetMatching<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for turning weighted likes on or off
+#[derive(Accounts)]
+pub struct SetLikeWeighting<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for turning two-step deletes on or off
+#[derive(Accounts)]
+pub struct SetTwoStepDelete<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for setting the per-user wish cap
+#[derive(Accounts)]
+pub struct SetWishCap<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for pausing the wall
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for proposing a new admin
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for accepting the admin role - checked against pending_admin in the handler
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    pub new_admin: Signer<'info>,
+}
+
+// Account structure for featuring a wish
+#[derive(Accounts)]
+pub struct SetFeatured<'info> {
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    pub admin: Signer<'info>,
+}
+
+// PDA Structure for individual wishes
+#[derive(Accounts)]
+#[instruction(
+    wish_id: u64,
+    title: String,
+    body: String,
+    category: Category,
+    visibility: Visibility,
+    ttl_secs: u64,
+    guardian: Option<Pubkey>,
+    content_hash: [u8; 32],
+    content_uri: String,
+    anonymous: bool
+)]
+pub struct SubmitWish<'info> {
+    /// CHECK: Created by the handler (see create_wish) once it has checked the wish id isn't taken
+    #[account(
+        mut,
+        seeds = [b"wish", board.key().as_ref(), submitter_key(&board.key(), user.key, wish_id, anonymous).as_ref(), &wish_id.to_le_bytes()], // Unique PDA per wish on the board, independent of the title
+        bump
+    )]
+    pub wish: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"leaderboard"], bump)]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed, // Created on the user's first submission, anonymous or not
+        payer = user,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed, // Created with the user's first wish, and for every anonymous wish under its own key
+        payer = user,
+        space = wish_index_space(wish_index),
+        seeds = [b"wish_index", submitter_key(&board.key(), user.key, wish_id, anonymous).as_ref()],
+        bump
+    )]
+    pub wish_index: Account<'info, WishIndex>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for submitting several wishes at once - the wish PDAs go in remaining_accounts
+#[derive(Accounts)]
+pub struct SubmitWishes<'info> {
+    #[account(mut)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"leaderboard"], bump)]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed, // Created on the user's first submission
+        payer = user,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed, // Created with the user's first wish
+        payer = user,
+        space = wish_index_space(wish_index),
+        seeds = [b"wish_index", user.key().as_ref()],
+        bump
+    )]
+    pub wish_index: Account<'info, WishIndex>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for committing to a hidden wish
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct CommitWish<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 32 + 8 + 32 + 8, // 8 (discriminator) + 32 (user pubkey) + 32 (board pubkey) + 8 (wish id) + 32 (commitment) + 8 (committed_at)
+        seeds = [b"wish", board.key().as_ref(), user.key().as_ref(), &wish_id.to_le_bytes()], // The PDA the revealed wish will live at
+        bump
+    )]
+    pub wish: Account<'info, WishCommitment>,
+
+    pub board: Account<'info, Board>,
+
+    #[account(seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for revealing a hidden wish
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct RevealWish<'info> {
+    /// CHECK: Loaded as a WishCommitment by the handler, then rewritten as an AWish - an Account
+    /// would write the commitment back over it on exit
+    #[account(
+        mut,
+        seeds = [b"wish", board.key().as_ref(), user.key().as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"leaderboard"], bump)]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed, // Committing doesn't create a profile
+        payer = user,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed, // Created with the user's first wish
+        payer = user,
+        space = wish_index_space(wish_index),
+        seeds = [b"wish_index", user.key().as_ref()],
+        bump
+    )]
+    pub wish_index: Account<'info, WishIndex>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for withdrawing an unrevealed commitment
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct CancelCommit<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), user.key().as_ref(), &wish_id.to_le_bytes()], // Seeded by the committer, so nobody else can cancel
+        bump,
+        close = user
+    )]
+    pub wish: Account<'info, WishCommitment>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+// An unrevealed wish, holding only the hash of its title
+#[account]
+pub struct WishCommitment {
+    pub user: Pubkey,         // Committer, who alone can reveal
+    pub board: Pubkey,        // Board the wish will appear on
+    pub wish_id: u64,         // Id the wish will have
+    pub commitment: [u8; 32], // SHA-256 of the title bytes followed by the 32-byte nonce
+    pub committed_at: i64,    // Unix timestamp of the commitment
+}
+
+// One entry of a batch submission
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NewWish {
+    pub wish_id: u64,
+    pub title: String,
+    pub body: String,
+    pub category: Category,
+    pub visibility: Visibility,
+    pub ttl_secs: u64,            // Seconds until the wish expires (0 = never)
+    pub guardian: Option<Pubkey>, // Co-signer required to delete the wish, if any
+    pub content_hash: [u8; 32],   // Hash of the off-chain content (all zeroes = none)
+    pub content_uri: String,      // Where the off-chain content lives (empty = none)
+}
+
+#[account]
+pub struct UserProfile {
+    pub last_submit: i64,                             // Unix timestamp of the user's latest submission
+    pub wish_count: u32,                              // Wishes the user has submitted
+    pub active_count: u32,                            // Unarchived wishes the user currently owns
+    pub activity_total: u64,                          // Actions ever logged - the next slot to write is this modulo ACTIVITY_LOG_SIZE
+    pub activity: [ActivityEntry; ACTIVITY_LOG_SIZE], // Ring buffer of the user's latest actions
+    pub first_seen: i64,                              // Unix timestamp of the wallet's first submission, like or tip (0 = none yet)
+    pub tips_given: u64,                              // Lamports the wallet has tipped to other wallets' wishes, not counting treasury matches
+}
+
+// Every wish a wallet currently owns, so frontends can list them from one account. The account is
+// sized in WISH_INDEX_CHUNK steps, so it usually has room to spare past the end of the vec.
+#[account]
+pub struct WishIndex {
+    pub user: Pubkey,        // Wallet the index belongs to
+    pub wishes: Vec<Pubkey>, // Wish PDAs it owns, in no particular order
+}
+
+// One action in a user's activity log
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ActivityEntry {
+    pub kind: u8,       // ACTIVITY_SUBMIT, ACTIVITY_DELETE or ACTIVITY_LIKE
+    pub timestamp: i64, // When the action happened
+    pub target: Pubkey, // The wish it was done to
+}
+
+// Every fixed-size field comes first, so its byte offset is the same in every wish (see the
+// WISH_*_OFFSET constants); the optional keys and strings follow. New fixed-size fields go after
+// delete_requested_at so existing offsets stay put, together with a WISH_VERSION bump.
+#[account]
+#[derive(Default)]
+pub struct AWish {
+    pub version: u8,                // Layout version (WISH_VERSION) - always the first data byte
+    pub user: Pubkey,               // Current owner of the wish (the submitter, unless transferred)
+    pub wish_id: u64,               // Client-chosen id used in the PDA seeds
+    pub created_at: i64,            // Unix timestamp from the on-chain clock at submission
+    pub likes: u64,                 // Number of users who liked the wish
+    pub fulfilled: bool,            // Set by the creator once the wish is granted
+    pub category: Category,         // Theme tag, at WISH_CATEGORY_OFFSET for memcmp filters
+    pub creator: Pubkey,            // Original submitter - used in the PDA seeds and never changes
+    pub total_tipped: u64,          // Lamports tipped to the owner over the wish's lifetime
+    pub comment_count: u64,         // Comments ever added - the next comment's index
+    pub reports: u16,               // Distinct users who flagged the wish as abusive
+    pub visibility: Visibility,     // Whether frontends should list the wish on the public wall
+    pub expires_at: i64,            // Unix timestamp after which anyone can reap the wish (0 = never)
+    pub followers: u32,             // Number of users following the wish
+    pub last_edited_at: i64,        // Unix timestamp of the latest update_wish (0 = never edited)
+    pub index: u64,                 // Position in submission order, from WallStats - at WISH_INDEX_OFFSET for paging
+    pub reactions: [u32; 4],        // Reaction counts, indexed by Reaction
+    pub content_hash: [u8; 32],     // Hash of the off-chain content behind content_uri (all zeroes = none)
+    pub pinned_until: i64,          // Unix timestamp until which the wish is pinned to the top (0 = never pinned)
+    pub claimed_at: i64,            // Unix timestamp of the current claim (0 = unclaimed)
+    pub archived: bool,             // Hidden by the owner but kept on-chain
+    pub sealed: bool,               // Locked by the owner against edits, archiving and deletion
+    pub board: Pubkey,              // Board the wish was submitted to - used in the PDA seeds and never changes
+    pub weighted_likes: u64,        // Sum of the weights of the likes counted in `likes`
+    pub delete_requested_at: i64,   // Unix timestamp of the pending request_delete (0 = none)
+    pub guardian: Option<Pubkey>,   // Must co-sign delete_wish when set
+    pub claimed_by: Option<Pubkey>, // Volunteer who has claimed the wish, if any
+    pub granted_by: Option<Pubkey>, // Claimer at the time the wish was marked fulfilled
+    pub title: String,              // Short wish headline
+    pub body: String,               // Longer wish text, optional
+    pub content_uri: String,        // IPFS/Arweave link to off-chain content (empty = none)
+} 
+
+// Theme a wish can be tagged with - serialized as a single byte
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Category {
+    Travel,
+    Career,
+    Health,
+    Learning,
+    Family,
+    #[default]
+    Other,
+}
+
+// Whether a wish is listed on the public wall. All account data on Solana is publicly readable,
+// so Private does not hide anything - it only tells frontends to leave the wish out of the wall.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+}
+
+// Emoji a user can react to a wish with - the discriminant indexes AWish::reactions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reaction {
+    #[default]
+    Heart,
+    Star,
+    Clap,
+    Fire,
+}
+
+// Account structure for editing wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64, new_title: String, new_body: String)]
+pub struct UpdateWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        realloc = account_size(new_title.len(), new_body.len(), wish.content_uri.len()), // Resize to fit the new title and body
+        realloc::payer = user, // The user pays for growth and is refunded on shrink
+        realloc::zero = false,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedUpdate
+    )]
+    pub wish: Account<'info, AWish>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for repointing a wish's off-chain content
+#[derive(Accounts)]
+#[instruction(wish_id: u64, content_hash: [u8; 32], content_uri: String)]
+pub struct UpdateContent<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        realloc = account_size(wish.title.len(), wish.body.len(), content_uri.len()), // Resize to fit the new URI
+        realloc::payer = user,
+        realloc::zero = false,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedUpdate
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for pinning wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct PinWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedPin
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for marking wishes fulfilled
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct MarkFulfilled<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedFulfillment
+    )]
+    pub wish: Account<'info, AWish>,
+
+    pub user: Signer<'info>,
+}
+
+// Account structure for claiming wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ClaimWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    pub claimer: Signer<'info>,
+}
+
+// Account structure for releasing a claim - signed by the claimer or the wish owner
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct UnclaimWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    pub signer: Signer<'info>,
+}
+
+// Account structure for changing wish visibility
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct SetVisibility<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedVisibilityChange
+    )]
+    pub wish: Account<'info, AWish>,
+
+    pub user: Signer<'info>,
+}
+
+// Account structure for verifying a wish - Account checks the owner program and discriminator, and the
+// seeds re-derive the PDA from its board, creator and id
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct VerifyWish<'info> {
+    #[account(
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+}
+
+// Account structure for sealing wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct SealWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedSeal
+    )]
+    pub wish: Account<'info, AWish>,
+
+    pub user: Signer<'info>,
+}
+
+// Account structure for archiving and unarchiving wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ArchiveWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedArchive
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(mut, address = wish.board @ ErrorCode::BoardMismatch)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"profile", user.key().as_ref()], bump)]
+    pub profile: Account<'info, UserProfile>,
+
+    pub user: Signer<'info>,
+}
+
+// Account structure for transferring wish ownership
+#[derive(Accounts)]
+#[instruction(wish_id: u64, new_owner: Pubkey)]
+pub struct TransferWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *current_owner.key @ ErrorCode::UnauthorizedTransfer
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"profile", current_owner.key().as_ref()], bump)]
+    pub from_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed, // The new owner may never have submitted a wish
+        payer = current_owner,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", new_owner.as_ref()],
+        bump
+    )]
+    pub to_profile: Account<'info, UserProfile>,
+
+    #[account(mut, seeds = [b"wish_index", current_owner.key().as_ref()], bump)]
+    pub from_index: Account<'info, WishIndex>,
+
+    #[account(
+        init_if_needed, // The new owner may never have held a wish
+        payer = current_owner,
+        space = wish_index_space(to_index),
+        seeds = [b"wish_index", new_owner.as_ref()],
+        bump
+    )]
+    pub to_index: Account<'info, WishIndex>,
+
+    #[account(mut)]
+    pub current_owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for tipping wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct TipWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    /// CHECK: Only receives lamports; must be the wish owner, which an anonymous wish doesn't record
+    #[account(
+        mut,
+        constraint = wish.user != Pubkey::default() @ ErrorCode::AnonymousWish,
+        address = wish.user @ ErrorCode::TipRecipientMismatch
+    )]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed, // Tippers don't need to have submitted a wish of their own
+        payer = tipper,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", tipper.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    // Tipping yourself would hand the tip straight back and still collect a treasury match
+    #[account(mut, constraint = tipper.key() != wish.user @ ErrorCode::SelfTip)]
+    pub tipper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for commenting on wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64, text: String)]
+pub struct AddComment<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        init,
+        payer = author, // The author pays rent for their own comment
+        space = 8 + 32 + 32 + 8 + 8 + 4 + text.len(), // 8 (discriminator) + 32 (wish pubkey) + 32 (author pubkey) + 8 (index) + 8 (created_at) + 4 (string length) + text bytes
+        seeds = [b"comment", wish.key().as_ref(), author.key().as_ref(), &wish.comment_count.to_le_bytes()],
+        bump
+    )]
+    pub comment: Account<'info, Comment>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for deleting comments
+#[derive(Accounts)]
+pub struct DeleteComment<'info> {
+    #[account(
+        mut,
+        seeds = [b"comment", comment.wish.as_ref(), comment.author.as_ref(), &comment.index.to_le_bytes()],
+        bump,
+        has_one = wish,
+        close = author, // Rent goes back to the author, even when the wish owner removes it
+        constraint = comment.author == *authority.key || wish.user == *authority.key @ ErrorCode::UnauthorizedCommentDeletion
+    )]
+    pub comment: Account<'info, Comment>,
+
+    pub wish: Account<'info, AWish>,
+
+    /// CHECK: Only receives the comment's rent; must be the comment author
+    #[account(mut, address = comment.author)]
+    pub author: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Comment {
+    pub wish: Pubkey,    // Wish the comment belongs to
+    pub author: Pubkey,  // User who wrote the comment
+    pub index: u64,      // Position in the wish's comment sequence, used in the PDA seeds
+    pub created_at: i64, // Unix timestamp from the on-chain clock
+    pub text: String,    // Comment content
+}
+
+// Account structure for reporting wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ReportWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        init_if_needed, // An existing account is rejected with a clear error in the handler
+        payer = reporter,
+        space = 8 + 32 + 32, // 8 (discriminator) + 32 (wish pubkey) + 32 (reporter pubkey)
+        seeds = [b"report", wish.key().as_ref(), reporter.key().as_ref()], // One report per reporter per wish
+        bump
+    )]
+    pub report: Account<'info, WishReport>,
+
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct WishReport {
+    pub wish: Pubkey,     // Wish that was reported
+    pub reporter: Pubkey, // User who reported it
+}
+
+// Account structure for reaping expired wishes - no owner signature needed
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ReapExpired<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(mut, address = wish.board @ ErrorCode::BoardMismatch)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"profile", wish.user.as_ref()], bump)]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut, seeds = [b"wish_index", wish.user.as_ref()], bump)]
+    pub wish_index: Account<'info, WishIndex>,
+
+    /// CHECK: Only receives the wish's rent; must be the wish owner
+    #[account(mut, constraint = owner.key() == wish.user @ ErrorCode::WishOwnerMismatch)]
+    pub owner: UncheckedAccount<'info>,
+}
+
+// Account structure for moderator removal of wishes - by the wall admin or the wish's board admin
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct AdminRemoveWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user != Pubkey::default() @ ErrorCode::AnonymousWish // See admin_remove_anonymous_wish
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        mut,
+        address = wish.board @ ErrorCode::BoardMismatch,
+        constraint = stats.admin == *admin.key || board.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"profile", wish.user.as_ref()], bump)]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut, seeds = [b"wish_index", wish.user.as_ref()], bump)]
+    pub wish_index: Account<'info, WishIndex>,
+
+    /// CHECK: Only receives the wish's rent (not the admin); must be the wish owner
+    #[account(mut, constraint = owner.key() == wish.user @ ErrorCode::WishOwnerMismatch)]
+    pub owner: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for moderator removal of anonymous wishes - the rent stays with the program
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct AdminRemoveAnonymousWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == Pubkey::default() @ ErrorCode::NotAnonymous // Others go through admin_remove_wish
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        mut,
+        address = wish.board @ ErrorCode::BoardMismatch,
+        constraint = stats.admin == *admin.key || board.admin == *admin.key @ ErrorCode::Unauthorized
+    )]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"wish_index", wish.creator.as_ref()], bump)] // Holds the rent until it's claimed
+    pub wish_index: Account<'info, WishIndex>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for claiming back an anonymous wish's rent after an admin removed it
+#[derive(Accounts)]
+#[instruction(board: Pubkey, wish_id: u64)]
+pub struct ClaimAnonymousRent<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish_index", anonymous_owner(&board, user.key, wish_id).as_ref()],
+        bump,
+        constraint = wish_index.wishes.is_empty() @ ErrorCode::WishNotRemoved, // Still listed means still on the wall
+        close = user
+    )]
+    pub wish_index: Account<'info, WishIndex>,
+
+    #[account(mut, seeds = [b"profile", user.key().as_ref()], bump)]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+// Account structure for reaping many of one wallet's wishes - the wishes go in remaining_accounts
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct AdminReapUser<'info> {
+    #[account(mut)]
+    pub board: Account<'info, Board>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump,
+        constraint = stats.admin == *admin.key @ ErrorCode::Unauthorized // The wall admin only
+    )]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(mut, seeds = [b"profile", target.as_ref()], bump)]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut, seeds = [b"wish_index", target.as_ref()], bump)]
+    pub wish_index: Account<'info, WishIndex>,
+
+    /// CHECK: Only receives the wishes' rent (not the admin); must be the target
+    #[account(mut, address = target)]
+    pub owner: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+// Account structure for liking wishes
+#[derive(Accounts)]
+pub struct LikeWish<'info> {
+    #[account(
+        init_if_needed, // An existing account is rejected with a clear error in the handler
+        payer = voter, // The voter pays rent for their own like
+        space = 8 + 32 + 32 + 8 + 8, // 8 (discriminator) + 32 (wish pubkey) + 32 (voter pubkey) + 8 (weight) + 8 (wish created_at)
+        seeds = [b"like", wish.key().as_ref(), voter.key().as_ref()], // One like per voter per wish
+        bump
+    )]
+    pub like: Account<'info, WishLike>,
+
+    #[account(mut)]
+    pub wish: Account<'info, AWish>,
+
+    #[account(seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(
+        init_if_needed, // Voters don't need to have submitted a wish of their own
+        payer = voter,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", voter.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for removing likes
+#[derive(Accounts)]
+pub struct UnlikeWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"like", wish.key().as_ref(), voter.key().as_ref()],
+        bump,
+        close = voter // Return the like's rent to the voter
+    )]
+    pub like: Account<'info, WishLike>,
 
-declare_id!("HZSqkqsgtJkFLwgyFMQHHbFEsU9jPdGZgBTpbrVRwJ8U"); // Replace with your actual program ID
+    #[account(mut)]
+    pub wish: Account<'info, AWish>,
 
-#[program]
-pub mod wall_of_wish {
-    use super::*;
+    #[account(mut)]
+    pub voter: Signer<'info>,
 
-    // Submit a wish (stored in a PDA)
-    pub fn submit_wish(ctx: Context<SubmitWish>, title: String) -> Result<()> {
-        let wish = &mut ctx.accounts.wish;
-        wish.user = *ctx.accounts.user.key;
-        wish.title = title;
-        Ok(())
-    }
-    
-    // Delete a wish from the blockchain
-    pub fn delete_wish(ctx: Context<DeleteWish>, title: String) -> Result<()> {
-        // No additional logic needed - the close constraint will handle account closing
-        Ok(())
-    }
+    pub system_program: Program<'info, System>,
 }
 
-// PDA Structure for individual wishes
+#[account]
+pub struct WishLike {
+    pub wish: Pubkey,         // Wish that was liked
+    pub voter: Pubkey,        // User who liked it
+    pub weight: u64,          // What the like added to the wish's weighted_likes
+    pub wish_created_at: i64, // created_at of the wish it was counted on, so a re-submitted wish starts fresh
+}
+
+// Account structure for following wishes
 #[derive(Accounts)]
-#[instruction(title: String)]
-pub struct SubmitWish<'info> {
+#[instruction(wish_id: u64)]
+pub struct FollowWish<'info> {
     #[account(
-        init,
-        payer = user,
-        space = 8 + 32 + 4 + title.len(), // 8 (discriminator) + 32 (user pubkey) + 4 (string length) + title bytes
-        seeds = [b"wish", user.key().as_ref(), title.as_bytes()], // Unique PDA per wish
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
         bump
     )]
     pub wish: Account<'info, AWish>,
-    
+
+    #[account(
+        init_if_needed, // An existing account is rejected with a clear error in the handler
+        payer = follower, // The follower pays rent for their own follow
+        space = 8 + 32 + 32 + 8, // 8 (discriminator) + 32 (wish pubkey) + 32 (follower pubkey) + 8 (wish created_at)
+        seeds = [b"follow", wish.key().as_ref(), follower.key().as_ref()], // One follow per follower per wish
+        bump
+    )]
+    pub follow: Account<'info, Follow>,
+
     #[account(mut)]
-    pub user: Signer<'info>,
-    
+    pub follower: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for unfollowing wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct UnfollowWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        mut,
+        seeds = [b"follow", wish.key().as_ref(), follower.key().as_ref()],
+        bump,
+        close = follower // Return the follow's rent to the follower
+    )]
+    pub follow: Account<'info, Follow>,
+
+    #[account(mut)]
+    pub follower: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[account]
-pub struct AWish {
-    pub user: Pubkey,  // User who submitted the wish
-    pub title: String, // Wish content
-} 
+pub struct Follow {
+    pub wish: Pubkey,         // Wish being followed
+    pub follower: Pubkey,     // User following it
+    pub wish_created_at: i64, // created_at of the wish it was counted on, so a re-submitted wish starts fresh
+}
+
+// Account structure for reacting to wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ReactWish<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        init_if_needed, // Created on the first reaction, reused when switching
+        payer = reactor,
+        space = 8 + 32 + 32 + 1 + 8, // 8 (discriminator) + 32 (wish pubkey) + 32 (reactor pubkey) + 1 (reaction) + 8 (wish created_at)
+        seeds = [b"reaction", wish.key().as_ref(), reactor.key().as_ref()], // One reaction per user per wish
+        bump
+    )]
+    pub reaction: Account<'info, WishReaction>,
+
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct WishReaction {
+    pub wish: Pubkey,         // Wish reacted to
+    pub reactor: Pubkey,      // User who reacted
+    pub reaction: Reaction,   // Their current reaction
+    pub wish_created_at: i64, // created_at of the wish it was counted on, so a re-submitted wish starts fresh
+}
+
+// Account structure for sponsoring wishes
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct SponsorWish<'info> {
+    #[account(
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        init_if_needed,
+        payer = sponsor, // The first sponsor pays the escrow's rent
+        space = 8 + 32 + 8 + 8 + 4, // 8 (discriminator) + 32 (wish pubkey) + 8 (wish created_at) + 8 (total) + 4 (sponsors)
+        seeds = [b"escrow", wish.key().as_ref()], // One escrow per wish
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = 8 + 32 + 32 + 8 + 8, // 8 (discriminator) + 32 (wish pubkey) + 32 (sponsor pubkey) + 8 (amount) + 8 (wish created_at)
+        seeds = [b"sponsorship", wish.key().as_ref(), sponsor.key().as_ref()], // One record per sponsor per wish
+        bump
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for releasing escrow to the wish owner
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ReleaseEscrow<'info> {
+    #[account(
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedRelease
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", wish.key().as_ref()],
+        bump,
+        close = user
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+// Account structure for a sponsor taking back their contribution
+#[derive(Accounts)]
+pub struct RefundEscrow<'info> {
+    /// CHECK: Address of the wish, which may be closed - only used in the seeds and inspected by the handler
+    pub wish: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", wish.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsorship", wish.key().as_ref(), sponsor.key().as_ref()],
+        bump,
+        constraint = sponsorship.wish_created_at == escrow.wish_created_at @ ErrorCode::EscrowMismatch, // Not a receipt from an earlier wish
+        close = sponsor // The record's rent goes back with the refund
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+}
+
+// Account structure for the wish owner refunding one of its sponsors
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct RefundSponsor<'info> {
+    #[account(
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = can_delete(&wish, owner.key) @ ErrorCode::UnauthorizedRefund // Also the submitter of an anonymous wish
+    )]
+    pub wish: Account<'info, AWish>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", wish.key().as_ref()],
+        bump,
+        constraint = escrow.wish_created_at == wish.created_at @ ErrorCode::EscrowMismatch
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsorship", wish.key().as_ref(), sponsor.key().as_ref()],
+        bump,
+        constraint = sponsorship.wish_created_at == wish.created_at @ ErrorCode::EscrowMismatch, // Not a receipt from an earlier wish
+        close = sponsor // The record's rent goes back with the refund
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    /// CHECK: Only receives the refund; the sponsorship seeds tie it to the sponsor
+    #[account(mut)]
+    pub sponsor: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+#[account]
+pub struct Escrow {
+    pub wish: Pubkey,         // Wish the lamports are promised to
+    pub wish_created_at: i64, // created_at of that wish, to tell it apart from a later one at the same address
+    pub total: u64,           // Lamports held, excluding rent
+    pub sponsors: u32,        // Sponsors with an outstanding contribution
+}
+
+#[account]
+pub struct Sponsorship {
+    pub wish: Pubkey,         // Wish being sponsored
+    pub sponsor: Pubkey,      // Who promised the lamports
+    pub amount: u64,          // Lamports contributed in total
+    pub wish_created_at: i64, // created_at of that wish, so a receipt can't be refunded from a later one's escrow
+}
 
 // Account structure for deleting wishes
 #[derive(Accounts)]
-#[instruction(title: String)]
+#[instruction(wish_id: u64)]
 pub struct DeleteWish<'info> {
     #[account(
         mut,
-        seeds = [b"wish", user.key().as_ref(), title.as_bytes()],
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
         bump,
-        close = user,  // This will close the account and return the rent to the user
-        constraint = wish.user == *user.key @ ErrorCode::UnauthorizedDeletion
+        constraint = can_delete(&wish, user.key) @ ErrorCode::UnauthorizedDeletion
     )]
     pub wish: Account<'info, AWish>,
+
+    /// CHECK: Only read, to make sure nothing is left in escrow - it usually doesn't exist
+    #[account(seeds = [b"escrow", wish.key().as_ref()], bump)]
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(mut, address = wish.board @ ErrorCode::BoardMismatch)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(
+        init_if_needed, // The owner of a transferred wish may never have submitted one
+        payer = user,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", user.key().as_ref()], // The signer's own - which also counts their anonymous wishes
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut, seeds = [b"wish_index", owner_key(&wish).as_ref()], bump)]
+    pub wish_index: Account<'info, WishIndex>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -65,9 +3162,428 @@ pub struct DeleteWish<'info> {
     pub system_program: Program<'info, System>,
 }
 
+// Account structure for requesting and cancelling a two-step delete
+#[derive(Accounts)]
+#[instruction(wish_id: u64)]
+pub struct ScheduleDelete<'info> {
+    #[account(
+        mut,
+        seeds = [b"wish", wish.board.as_ref(), wish.creator.as_ref(), &wish_id.to_le_bytes()],
+        bump,
+        constraint = can_delete(&wish, user.key) @ ErrorCode::UnauthorizedDeletion
+    )]
+    pub wish: Account<'info, AWish>,
+
+    pub user: Signer<'info>,
+}
+
+// Account structure for deleting several wishes at once - wish/escrow pairs go in remaining_accounts
+#[derive(Accounts)]
+pub struct DeleteAll<'info> {
+    #[account(mut)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(
+        init_if_needed, // The owner of a transferred wish may never have submitted one
+        payer = user,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(mut, seeds = [b"wish_index", user.key().as_ref()], bump)]
+    pub wish_index: Account<'info, WishIndex>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for migrating a first-release wish to the current layout
+#[derive(Accounts)]
+#[instruction(wish_id: u64, title: String)]
+pub struct MigrateWish<'info> {
+    /// CHECK: Parsed by the handler (see read_legacy_wish) - it doesn't deserialize as an AWish
+    #[account(
+        mut,
+        seeds = [b"wish", user.key().as_ref(), title.as_bytes()], // The first release's seeds
+        bump
+    )]
+    pub legacy_wish: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the handler (see create_wish) after checking the wish id isn't taken
+    #[account(
+        mut,
+        seeds = [b"wish", board.key().as_ref(), user.key().as_ref(), &wish_id.to_le_bytes()],
+        bump
+    )]
+    pub wish: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub board: Account<'info, Board>,
+
+    #[account(mut, seeds = [b"stats"], bump)]
+    pub stats: Account<'info, WallStats>,
+
+    #[account(
+        init_if_needed, // First-release owners don't have a profile yet
+        payer = user,
+        space = PROFILE_SIZE,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed, // Created with the user's first wish
+        payer = user,
+        space = wish_index_space(wish_index),
+        seeds = [b"wish_index", user.key().as_ref()],
+        bump
+    )]
+    pub wish_index: Account<'info, WishIndex>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Account structure for upgrading a wish to the current layout version
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Parsed by the handler (see read_old_wish) - an older layout doesn't deserialize as an AWish
+    #[account(mut)]
+    pub wish: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// A wish in layout version 1, from before the version byte was added: today's fields without it
+#[derive(AnchorDeserialize)]
+pub struct WishV1 {
+    pub user: Pubkey,
+    pub wish_id: u64,
+    pub created_at: i64,
+    pub likes: u64,
+    pub fulfilled: bool,
+    pub category: Category,
+    pub creator: Pubkey,
+    pub total_tipped: u64,
+    pub comment_count: u64,
+    pub reports: u16,
+    pub visibility: Visibility,
+    pub expires_at: i64,
+    pub followers: u32,
+    pub last_edited_at: i64,
+    pub index: u64,
+    pub reactions: [u32; 4],
+    pub content_hash: [u8; 32],
+    pub pinned_until: i64,
+    pub claimed_at: i64,
+    pub archived: bool,
+    pub sealed: bool,
+    pub board: Pubkey,
+    pub weighted_likes: u64,
+    pub delete_requested_at: i64,
+    pub guardian: Option<Pubkey>,
+    pub claimed_by: Option<Pubkey>,
+    pub granted_by: Option<Pubkey>,
+    pub title: String,
+    pub body: String,
+    pub content_uri: String,
+}
+
+// A wish as the first release stored it
+#[derive(AnchorDeserialize)]
+pub struct LegacyWish {
+    pub user: Pubkey,
+    pub title: String,
+}
+
+// Emitted when a wish is submitted - `wish` is the PDA address
+#[event]
+pub struct WishSubmitted {
+    pub user: Pubkey,
+    pub title: String,
+    pub wish: Pubkey,
+}
+
+// Emitted when a wish is deleted - `wish` is the PDA address that was closed
+#[event]
+pub struct WishDeleted {
+    pub user: Pubkey,
+    pub wish: Pubkey,
+}
+
+// Emitted when an owner requests a two-step delete - the wish can be closed from `deletable_at` on
+#[event]
+pub struct DeleteRequested {
+    pub user: Pubkey,
+    pub wish: Pubkey,
+    pub deletable_at: i64,
+}
+
+// Emitted when a first-release wish is moved to the current layout
+#[event]
+pub struct WishMigrated {
+    pub user: Pubkey,
+    pub legacy_wish: Pubkey,
+    pub wish: Pubkey,
+}
+
 // Custom error codes for the program
 #[error_code]
 pub enum ErrorCode {
     #[msg("Only the wish creator can delete it")]
     UnauthorizedDeletion,
-}
\ No newline at end of file
+    #[msg("Only the wish creator can update it")]
+    UnauthorizedUpdate,
+    #[msg("Only the wish creator can mark it fulfilled")]
+    UnauthorizedFulfillment,
+    #[msg("Wish is already fulfilled")]
+    AlreadyFulfilled,
+    #[msg("Please wait before submitting another wish")]
+    SubmitTooSoon,
+    #[msg("Only the wish owner can transfer it")]
+    UnauthorizedTransfer,
+    #[msg("Tip amount must be greater than zero")]
+    ZeroTip,
+    #[msg("Tips can only be sent to the wish owner")]
+    TipRecipientMismatch,
+    #[msg("Only the wall admin can do this")]
+    Unauthorized,
+    #[msg("The wall is paused - new wishes can't be submitted right now")]
+    WallPaused,
+    #[msg("Comment exceeds the maximum length of 200 bytes")]
+    CommentTooLong,
+    #[msg("Comment cannot be empty")]
+    CommentEmpty,
+    #[msg("Only the comment author or the wish owner can delete a comment")]
+    UnauthorizedCommentDeletion,
+    #[msg("Wish account was not fully closed")]
+    CloseIncomplete,
+    #[msg("Number of wish accounts doesn't match the number of wishes")]
+    AccountMismatch,
+    #[msg("The same wish id appears more than once in the batch")]
+    DuplicateWishInBatch,
+    #[msg("Wish account doesn't match the PDA derived from the board, user and wish id")]
+    WishAddressMismatch,
+    #[msg("Only the wish owner can change its visibility")]
+    UnauthorizedVisibilityChange,
+    #[msg("Wish has not expired yet")]
+    NotExpired,
+    #[msg("Wish title exceeds the maximum size of 560 bytes")]
+    TitleTooLong,
+    #[msg("Wish title cannot be empty")]
+    TitleEmpty,
+    #[msg("Wish body exceeds the maximum length of 512 bytes")]
+    BodyTooLong,
+    #[msg("Wish has a guardian who must co-sign its deletion")]
+    GuardianSignatureMissing,
+    #[msg("Sponsorship amount must be greater than zero")]
+    ZeroSponsorship,
+    #[msg("Only the wish owner can release its escrow")]
+    UnauthorizedRelease,
+    #[msg("Escrow can only be released once the wish is fulfilled")]
+    WishNotFulfilled,
+    #[msg("Escrow belongs to a different wish at this address")]
+    EscrowMismatch,
+    #[msg("Wish still exists - only its owner can refund you, with refund_sponsor")]
+    WishStillExists,
+    #[msg("Featured account is not a wish")]
+    NotAWish,
+    #[msg("You already have a wish with this id - pick a new wish_id")]
+    DuplicateWish,
+    #[msg("Content URI exceeds the maximum length of 128 bytes")]
+    ContentUriTooLong,
+    #[msg("Wish account is not rent-exempt")]
+    NotRentExempt,
+    #[msg("No admin handoff has been proposed")]
+    NoPendingAdmin,
+    #[msg("Treasury doesn't hold enough lamports above its rent reserve")]
+    InsufficientTreasury,
+    #[msg("Too many accounts in one batch")]
+    BatchTooLarge,
+    #[msg("Only the wish owner can pin it")]
+    UnauthorizedPin,
+    #[msg("Pin duration must be greater than zero")]
+    ZeroPinDuration,
+    #[msg("Pin duration is too long")]
+    PinTooLong,
+    #[msg("You already liked this wish")]
+    AlreadyLiked,
+    #[msg("You already follow this wish")]
+    AlreadyFollowing,
+    #[msg("You already reported this wish")]
+    AlreadyReported,
+    #[msg("Wish has already been claimed")]
+    AlreadyClaimed,
+    #[msg("Wish isn't claimed")]
+    NotClaimed,
+    #[msg("Only the claimer or the wish owner can release a claim")]
+    UnauthorizedUnclaim,
+    #[msg("Wish title contains control characters")]
+    InvalidCharacters,
+    #[msg("Only the wish owner can archive or unarchive it")]
+    UnauthorizedArchive,
+    #[msg("Wish is already archived")]
+    AlreadyArchived,
+    #[msg("Wish isn't archived")]
+    NotArchived,
+    #[msg("Wish is owned by a different wallet")]
+    WishOwnerMismatch,
+    #[msg("Account is not a wish in the first release's layout")]
+    NotALegacyWish,
+    #[msg("You already hold the maximum number of active wishes")]
+    WishLimitReached,
+    #[msg("Wish is sealed and can no longer be edited, archived or deleted")]
+    WishSealed,
+    #[msg("Only the wish owner can seal it")]
+    UnauthorizedSeal,
+    #[msg("Wish is already sealed")]
+    AlreadySealed,
+    #[msg("Wish title exceeds the maximum length of 280 characters")]
+    TitleTooManyChars,
+    #[msg("Board name cannot be empty")]
+    BoardNameEmpty,
+    #[msg("Board name exceeds the maximum length of 64 bytes")]
+    BoardNameTooLong,
+    #[msg("Wish is on a different board")]
+    BoardMismatch,
+    #[msg("Title and nonce don't match the commitment")]
+    CommitmentMismatch,
+    #[msg("No unrevealed commitment for this wish")]
+    NotCommitted,
+    #[msg("Sponsors still have lamports in escrow for this wish - release or refund them first")]
+    OutstandingFunds,
+    #[msg("Your wish index is full - delete a wish first")]
+    WishIndexFull,
+    #[msg("Program is deployed under a different ID than declare_id! - update it and redeploy")]
+    ProgramIdMismatch,
+    #[msg("Request the deletion and wait out the grace period before deleting")]
+    DeleteNotReady,
+    #[msg("Wish is already in the current layout")]
+    WishUpToDate,
+    #[msg("Account is not a wish in a layout migrate_account can upgrade")]
+    UnknownWishLayout,
+    #[msg("Anonymous wishes don't record an owner to pay")]
+    AnonymousWish,
+    #[msg("Wishes can't be transferred to the zeroed key")]
+    InvalidNewOwner,
+    #[msg("You can't tip your own wish")]
+    SelfTip,
+    #[msg("Only the wish owner can refund its sponsors")]
+    UnauthorizedRefund,
+    #[msg("Anonymous wishes can't expire - submit them with a ttl of 0")]
+    AnonymousExpiry,
+    #[msg("Wish isn't anonymous")]
+    NotAnonymous,
+    #[msg("Wish is still on the wall - delete it to get its rent back")]
+    WishNotRemoved,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_board() -> Leaderboard {
+        Leaderboard { entries: [LeaderboardEntry::default(); LEADERBOARD_SIZE] }
+    }
+
+    fn users(board: &Leaderboard) -> Vec<Pubkey> {
+        board.entries.iter().filter(|entry| entry.wish_count > 0).map(|entry| entry.user).collect()
+    }
+
+    #[test]
+    fn equal_counts_rank_by_index() {
+        let (older, newer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut board = empty_board();
+        // `newer` reaches one wish first, but `older` reaches two first
+        update_leaderboard(&mut board, newer, 1, 0);
+        update_leaderboard(&mut board, older, 1, 1);
+        assert_eq!(users(&board), vec![newer, older]);
+        update_leaderboard(&mut board, older, 2, 2);
+        update_leaderboard(&mut board, newer, 2, 3);
+        assert_eq!(users(&board), vec![older, newer]);
+        assert_eq!(board.entries[0].index, 2);
+        assert_eq!(board.entries[1].index, 3);
+    }
+
+    #[test]
+    fn full_board_keeps_the_earlier_tie() {
+        let mut board = empty_board();
+        let ranked: Vec<Pubkey> = (0..LEADERBOARD_SIZE).map(|_| Pubkey::new_unique()).collect();
+        for (i, user) in ranked.iter().enumerate() {
+            update_leaderboard(&mut board, *user, 5, i as u64);
+        }
+        // Tying the lowest entry with a later index doesn't displace it
+        update_leaderboard(&mut board, Pubkey::new_unique(), 5, LEADERBOARD_SIZE as u64);
+        assert_eq!(users(&board), ranked);
+
+        // Beating it takes over its slot and bubbles up past every lower count
+        let climber = Pubkey::new_unique();
+        update_leaderboard(&mut board, climber, 6, LEADERBOARD_SIZE as u64 + 1);
+        assert_eq!(users(&board)[0], climber);
+        assert_eq!(users(&board)[1..], ranked[..LEADERBOARD_SIZE - 1]);
+    }
+
+    fn error_code(result: Result<AWish>) -> u32 {
+        match result {
+            Err(Error::AnchorError(error)) => error.error_code_number,
+            _ => panic!("expected an AnchorError"),
+        }
+    }
+
+    // A wish with every Option<Pubkey> None, written into an account sized by account_size
+    fn current_wish_data() -> (AWish, Vec<u8>) {
+        let wish = AWish {
+            version: WISH_VERSION,
+            user: Pubkey::new_unique(),
+            title: "I wish for a roomy account".to_string(),
+            ..AWish::default()
+        };
+        let mut data = vec![0; account_size(wish.title.len(), 0, 0)];
+        wish.try_serialize(&mut &mut data[..]).unwrap();
+        (wish, data)
+    }
+
+    #[test]
+    fn current_wish_with_unused_option_space_is_up_to_date() {
+        let (_, data) = current_wish_data();
+        assert_eq!(error_code(parse_old_wish(&data)), u32::from(ErrorCode::WishUpToDate));
+    }
+
+    #[test]
+    fn v1_wish_with_unused_option_space_is_upgraded() {
+        let (wish, data) = current_wish_data();
+        let v1: Vec<u8> = data[..8].iter().chain(&data[9..]).copied().collect();
+        let upgraded = parse_old_wish(&v1).unwrap();
+        assert_eq!(upgraded.version, WISH_VERSION);
+        assert_eq!(upgraded.user, wish.user);
+        assert_eq!(upgraded.title, wish.title);
+
+        // Anything but zeroes after the data, or a different size, isn't a v1 wish
+        let mut dirty = v1.clone();
+        *dirty.last_mut().unwrap() = 1;
+        assert_eq!(error_code(parse_old_wish(&dirty)), u32::from(ErrorCode::UnknownWishLayout));
+        let short = &v1[..v1.len() - 1];
+        assert_eq!(error_code(parse_old_wish(short)), u32::from(ErrorCode::UnknownWishLayout));
+    }
+
+    #[test]
+    fn wish_rank_orders_score_then_index() {
+        assert_eq!(wish_rank((2, 9), (1, 0)), Ordering::Less);
+        assert_eq!(wish_rank((1, 0), (1, 9)), Ordering::Less);
+        assert_eq!(wish_rank((1, 9), (1, 0)), Ordering::Greater);
+        assert_eq!(wish_rank((1, 3), (1, 3)), Ordering::Equal);
+    }
+}
@@ -0,0 +1,732 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { start, Clock, ProgramTestContext } from "solana-bankrun";
+import { WallOfWish } from "../target/types/wall_of_wish";
+import { expect } from "chai";
+import { createHash } from "crypto";
+
+// Runs the program inside bankrun instead of a validator, so the Clock sysvar the program reads
+// through `now()` can be warped rather than waited for.
+describe("wall-of-wish (warped clock)", () => {
+  const idl = require("../target/idl/wall_of_wish.json");
+  const programId = new anchor.web3.PublicKey(idl.metadata?.address ?? "HZSqkqsgtJkFLwgyFMQHHbFEsU9jPdGZgBTpbrVRwJ8U");
+  // Only used to build instructions and decode accounts; transactions go through the bank
+  const program = new Program(idl, programId, {} as anchor.Provider) as unknown as Program<WallOfWish>;
+
+  const pda = (...seeds: Buffer[]) =>
+    anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+  const statsPDA = pda(Buffer.from("stats"));
+  const leaderboardPDA = pda(Buffer.from("leaderboard"));
+  const treasuryPDA = pda(Buffer.from("treasury"));
+  const boardPDA = pda(Buffer.from("board"), createHash("sha256").update("main").digest());
+
+  let context: ProgramTestContext;
+  let payer: anchor.web3.Keypair;
+
+  // Sign and process a single instruction, returning the bank's result instead of throwing. The
+  // payer always signs and pays the fee; `signers` are any other wallets the instruction needs.
+  const send = async (ix: anchor.web3.TransactionInstruction, ...signers: anchor.web3.Keypair[]) => {
+    const tx = new anchor.web3.Transaction().add(ix);
+    [tx.recentBlockhash] = await context.banksClient.getLatestBlockhash();
+    tx.feePayer = payer.publicKey;
+    tx.sign(payer, ...signers);
+    return context.banksClient.tryProcessTransaction(tx);
+  };
+
+  // Move the bank's clock forward by `secs` seconds. Advancing a slot as well gives the next
+  // transaction a fresh blockhash, so a retried instruction isn't rejected as already processed.
+  const warp = async (secs: number) => {
+    const { slot } = await context.banksClient.getClock();
+    context.warpToSlot(slot + BigInt(1));
+    const clock = await context.banksClient.getClock();
+    context.setClock(
+      new Clock(
+        clock.slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        clock.unixTimestamp + BigInt(secs)
+      )
+    );
+  };
+
+  before(async () => {
+    // Load the program built by `anchor build`
+    process.env.SBF_OUT_DIR = "target/deploy";
+    context = await start([{ name: "wall_of_wish", programId }], []);
+    payer = context.payer;
+
+    const result = await send(
+      await program.methods
+        .initializeWall()
+        .accounts({
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          authority: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(result.result).to.be.null;
+
+    const board = await send(
+      await program.methods
+        .createBoard("main")
+        .accounts({
+          board: boardPDA,
+          creator: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(board.result).to.be.null;
+  });
+
+  it("Reaps a wish only after the clock is warped past its expiry", async () => {
+    const wishId = new anchor.BN(1);
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const ttlSecs = 24 * 60 * 60;
+
+    const submitted = await send(
+      await program.methods
+        .submitWish(
+          wishId,
+          "I wish for a day-long wish",
+          "",
+          { other: {} },
+          { public: {} },
+          new anchor.BN(ttlSecs),
+          null,
+          new Array(32).fill(0),
+          "",
+          false
+        )
+        .accounts({
+          wish: wishPDA,
+          board: boardPDA,
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          profile: pda(Buffer.from("profile"), payer.publicKey.toBuffer()),
+          wishIndex: pda(Buffer.from("wish_index"), payer.publicKey.toBuffer()),
+          user: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(submitted.result).to.be.null;
+
+    const reap = async () =>
+      send(
+        await program.methods
+          .reapExpired(wishId)
+          .accounts({
+            wish: wishPDA,
+            board: boardPDA,
+            stats: statsPDA,
+            profile: pda(Buffer.from("profile"), payer.publicKey.toBuffer()),
+            wishIndex: pda(Buffer.from("wish_index"), payer.publicKey.toBuffer()),
+            owner: payer.publicKey,
+          })
+          .instruction()
+      );
+
+    // A minute short of the expiry the wish is still live
+    await warp(ttlSecs - 60);
+    const early = await reap();
+    expect(early.result).to.not.be.null;
+    expect(early.meta.logMessages.join("\n")).to.include("NotExpired");
+
+    await warp(120);
+    const reaped = await reap();
+    expect(reaped.result).to.be.null;
+    expect(await context.banksClient.getAccount(wishPDA)).to.be.null;
+  });
+
+  it("Weights a like by how long the voter's profile has existed", async () => {
+    // The payer's profile was first seen at the previous test's submission; three weeks later
+    // its like carries three extra units of weight
+    await warp(3 * 7 * 24 * 60 * 60);
+
+    const wishId = new anchor.BN(2);
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const likePDA = pda(Buffer.from("like"), wishPDA.toBuffer(), payer.publicKey.toBuffer());
+    const profilePDA = pda(Buffer.from("profile"), payer.publicKey.toBuffer());
+
+    const submitted = await send(
+      await program.methods
+        .submitWish(wishId, "I wish for seasoned voters", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", false)
+        .accounts({
+          wish: wishPDA,
+          board: boardPDA,
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          profile: profilePDA,
+          wishIndex: pda(Buffer.from("wish_index"), payer.publicKey.toBuffer()),
+          user: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(submitted.result).to.be.null;
+
+    const weighting = await send(
+      await program.methods
+        .setLikeWeighting(true)
+        .accounts({ stats: statsPDA, admin: payer.publicKey })
+        .instruction()
+    );
+    expect(weighting.result).to.be.null;
+
+    const liked = await send(
+      await program.methods
+        .likeWish()
+        .accounts({
+          like: likePDA,
+          wish: wishPDA,
+          stats: statsPDA,
+          profile: profilePDA,
+          voter: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(liked.result).to.be.null;
+
+    const like = program.coder.accounts.decode("WishLike", Buffer.from((await context.banksClient.getAccount(likePDA)).data));
+    expect(like.weight.toNumber()).to.equal(4);
+    const wish = program.coder.accounts.decode("AWish", Buffer.from((await context.banksClient.getAccount(wishPDA)).data));
+    expect(wish.likes.toNumber()).to.equal(1);
+    expect(wish.weightedLikes.toNumber()).to.equal(4);
+  });
+
+  it("Confirms a delete only after the grace period", async () => {
+    const graceSecs = 24 * 60 * 60;
+    await warp(60); // Past the submission cooldown
+    const wishId = new anchor.BN(3);
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+
+    const submitted = await send(
+      await program.methods
+        .submitWish(wishId, "I wish to leave slowly", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", false)
+        .accounts({
+          wish: wishPDA,
+          board: boardPDA,
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          profile: pda(Buffer.from("profile"), payer.publicKey.toBuffer()),
+          wishIndex: pda(Buffer.from("wish_index"), payer.publicKey.toBuffer()),
+          user: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(submitted.result).to.be.null;
+
+    const schedule = async (method: "requestDelete" | "cancelDelete") =>
+      send(await program.methods[method](wishId).accounts({ wish: wishPDA, user: payer.publicKey }).instruction());
+    const confirm = async () =>
+      send(
+        await program.methods
+          .confirmDelete(wishId)
+          .accounts({
+            wish: wishPDA,
+            board: boardPDA,
+            escrow: pda(Buffer.from("escrow"), wishPDA.toBuffer()),
+            stats: statsPDA,
+            profile: pda(Buffer.from("profile"), payer.publicKey.toBuffer()),
+            wishIndex: pda(Buffer.from("wish_index"), payer.publicKey.toBuffer()),
+            user: payer.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .instruction()
+      );
+
+    // A cancelled request doesn't count, however long ago it was made
+    expect((await schedule("requestDelete")).result).to.be.null;
+    expect((await schedule("cancelDelete")).result).to.be.null;
+    await warp(graceSecs + 60);
+    const cancelled = await confirm();
+    expect(cancelled.result).to.not.be.null;
+    expect(cancelled.meta.logMessages.join("\n")).to.include("DeleteNotReady");
+
+    // A minute short of the grace period the wish stays
+    expect((await schedule("requestDelete")).result).to.be.null;
+    await warp(graceSecs - 60);
+    const early = await confirm();
+    expect(early.result).to.not.be.null;
+    expect(early.meta.logMessages.join("\n")).to.include("DeleteNotReady");
+
+    await warp(120);
+    const confirmed = await confirm();
+    expect(confirmed.result).to.be.null;
+    expect(await context.banksClient.getAccount(wishPDA)).to.be.null;
+  });
+
+  it("Doesn't carry likes, follows or reactions over to a wish re-submitted at the same address", async () => {
+    await warp(60); // Past the submission cooldown
+    const wishId = new anchor.BN(5);
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const likePDA = pda(Buffer.from("like"), wishPDA.toBuffer(), payer.publicKey.toBuffer());
+    const followPDA = pda(Buffer.from("follow"), wishPDA.toBuffer(), payer.publicKey.toBuffer());
+    const reactionPDA = pda(Buffer.from("reaction"), wishPDA.toBuffer(), payer.publicKey.toBuffer());
+    const profilePDA = pda(Buffer.from("profile"), payer.publicKey.toBuffer());
+    const wishIndexPDA = pda(Buffer.from("wish_index"), payer.publicKey.toBuffer());
+    const systemProgram = anchor.web3.SystemProgram.programId;
+
+    const submit = async () =>
+      send(
+        await program.methods
+          .submitWish(wishId, "I wish to start over", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", false)
+          .accounts({
+            wish: wishPDA,
+            board: boardPDA,
+            stats: statsPDA,
+            leaderboard: leaderboardPDA,
+            treasury: treasuryPDA,
+            profile: profilePDA,
+            wishIndex: wishIndexPDA,
+            user: payer.publicKey,
+            systemProgram,
+          })
+          .instruction()
+      );
+    const like = async () =>
+      send(
+        await program.methods
+          .likeWish()
+          .accounts({ like: likePDA, wish: wishPDA, stats: statsPDA, profile: profilePDA, voter: payer.publicKey, systemProgram })
+          .instruction()
+      );
+    const follow = async (method: "followWish" | "unfollowWish") =>
+      send(
+        await program.methods[method](wishId)
+          .accounts({ wish: wishPDA, follow: followPDA, follower: payer.publicKey, systemProgram })
+          .instruction()
+      );
+    const react = async () =>
+      send(
+        await program.methods
+          .reactWish(wishId, { fire: {} })
+          .accounts({ wish: wishPDA, reaction: reactionPDA, reactor: payer.publicKey, systemProgram })
+          .instruction()
+      );
+    const fetchWish = async () =>
+      program.coder.accounts.decode("AWish", Buffer.from((await context.banksClient.getAccount(wishPDA)).data));
+
+    expect((await submit()).result).to.be.null;
+    expect((await like()).result).to.be.null;
+    expect((await follow("followWish")).result).to.be.null;
+    expect((await react()).result).to.be.null;
+
+    const deleted = await send(
+      await program.methods
+        .deleteWish(wishId)
+        .accounts({
+          wish: wishPDA,
+          board: boardPDA,
+          escrow: pda(Buffer.from("escrow"), wishPDA.toBuffer()),
+          stats: statsPDA,
+          profile: profilePDA,
+          wishIndex: wishIndexPDA,
+          user: payer.publicKey,
+          systemProgram,
+        })
+        .instruction()
+    );
+    expect(deleted.result).to.be.null;
+
+    // The like, follow and reaction PDAs survive the delete, but the new wish starts from zero
+    await warp(60);
+    expect((await submit()).result).to.be.null;
+    let wish = await fetchWish();
+    expect(wish.likes.toNumber()).to.equal(0);
+    expect(wish.followers).to.equal(0);
+
+    // Unfollowing through the stale follow closes it without taking a follower off the new wish
+    expect((await follow("unfollowWish")).result).to.be.null;
+    expect(await context.banksClient.getAccount(followPDA)).to.be.null;
+    expect((await fetchWish()).followers).to.equal(0);
+
+    // The stale like and reaction are treated as fresh rather than as duplicates
+    expect((await like()).result).to.be.null;
+    expect((await react()).result).to.be.null;
+    wish = await fetchWish();
+    expect(wish.likes.toNumber()).to.equal(1);
+    expect(wish.reactions).to.deep.equal([0, 0, 0, 1]);
+    const likeAccount = program.coder.accounts.decode("WishLike", Buffer.from((await context.banksClient.getAccount(likePDA)).data));
+    expect(likeAccount.wishCreatedAt.eq(wish.createdAt)).to.be.true;
+    expect(wish.weightedLikes.eq(likeAccount.weight)).to.be.true;
+  });
+
+  it("Holds anonymous submissions to the submitter's own cooldown and wish cap", async () => {
+    const profilePDA = pda(Buffer.from("profile"), payer.publicKey.toBuffer());
+    const activeCount = async () =>
+      program.coder.accounts.decode("UserProfile", Buffer.from((await context.banksClient.getAccount(profilePDA)).data)).activeCount;
+    const setWishCap = async (max: number) =>
+      send(await program.methods.setWishCap(max).accounts({ stats: statsPDA, admin: payer.publicKey }).instruction());
+    const submitAnonymous = async (wishId: anchor.BN) => {
+      // Only the wish and its index are filed under the anonymous key; the profile is the payer's
+      const key = new anchor.web3.PublicKey(
+        createHash("sha256")
+          .update(Buffer.concat([Buffer.from("anonymous"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8)]))
+          .digest()
+      );
+      return send(
+        await program.methods
+          .submitWish(wishId, "I wish to go unnoticed", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", true)
+          .accounts({
+            wish: pda(Buffer.from("wish"), boardPDA.toBuffer(), key.toBuffer(), wishId.toArrayLike(Buffer, "le", 8)),
+            board: boardPDA,
+            stats: statsPDA,
+            leaderboard: leaderboardPDA,
+            treasury: treasuryPDA,
+            profile: profilePDA,
+            wishIndex: pda(Buffer.from("wish_index"), key.toBuffer()),
+            user: payer.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .instruction()
+      );
+    };
+
+    await warp(60); // Past the submission cooldown
+    const active = await activeCount();
+    expect((await setWishCap(active)).result).to.be.null;
+    try {
+      const capped = await submitAnonymous(new anchor.BN(6));
+      expect(capped.result).to.not.be.null;
+      expect(capped.meta.logMessages.join("\n")).to.include("WishLimitReached");
+
+      expect((await setWishCap(active + 1)).result).to.be.null;
+      expect((await submitAnonymous(new anchor.BN(6))).result).to.be.null;
+      expect(await activeCount()).to.equal(active + 1);
+
+      // A fresh anonymous key doesn't come with a fresh cooldown
+      const early = await submitAnonymous(new anchor.BN(7));
+      expect(early.result).to.not.be.null;
+      expect(early.meta.logMessages.join("\n")).to.include("SubmitTooSoon");
+    } finally {
+      expect((await setWishCap(0)).result).to.be.null;
+    }
+  });
+
+  it("Won't refund a sponsorship receipt out of a later wish's escrow at the same address", async () => {
+    await warp(60); // Past the submission cooldown
+    const wishId = new anchor.BN(8);
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const escrowPDA = pda(Buffer.from("escrow"), wishPDA.toBuffer());
+    const sponsorshipPDA = (sponsor: anchor.web3.PublicKey) =>
+      pda(Buffer.from("sponsorship"), wishPDA.toBuffer(), sponsor.toBuffer());
+    const profilePDA = pda(Buffer.from("profile"), payer.publicKey.toBuffer());
+    const wishIndexPDA = pda(Buffer.from("wish_index"), payer.publicKey.toBuffer());
+    const systemProgram = anchor.web3.SystemProgram.programId;
+
+    const submit = async () =>
+      send(
+        await program.methods
+          .submitWish(wishId, "I wish to be sponsored twice", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", false)
+          .accounts({
+            wish: wishPDA,
+            board: boardPDA,
+            stats: statsPDA,
+            leaderboard: leaderboardPDA,
+            treasury: treasuryPDA,
+            profile: profilePDA,
+            wishIndex: wishIndexPDA,
+            user: payer.publicKey,
+            systemProgram,
+          })
+          .instruction()
+      );
+    const sponsor = async (wallet: anchor.web3.Keypair, amount: number) =>
+      send(
+        await program.methods
+          .sponsorWish(wishId, new anchor.BN(amount))
+          .accounts({
+            wish: wishPDA,
+            escrow: escrowPDA,
+            sponsorship: sponsorshipPDA(wallet.publicKey),
+            sponsor: wallet.publicKey,
+            systemProgram,
+          })
+          .instruction(),
+        ...(wallet === payer ? [] : [wallet])
+      );
+    const fetchEscrow = async () =>
+      program.coder.accounts.decode("Escrow", Buffer.from((await context.banksClient.getAccount(escrowPDA)).data));
+
+    // The owner sponsors their own wish, fulfills it and releases the escrow, leaving a receipt
+    expect((await submit()).result).to.be.null;
+    expect((await sponsor(payer, 1_000_000)).result).to.be.null;
+    expect(
+      (await send(await program.methods.markFulfilled(wishId).accounts({ wish: wishPDA, user: payer.publicKey }).instruction())).result
+    ).to.be.null;
+    expect(
+      (
+        await send(
+          await program.methods.releaseEscrow(wishId).accounts({ wish: wishPDA, escrow: escrowPDA, user: payer.publicKey }).instruction()
+        )
+      ).result
+    ).to.be.null;
+    expect(await context.banksClient.getAccount(sponsorshipPDA(payer.publicKey))).to.not.be.null;
+
+    const deleted = await send(
+      await program.methods
+        .deleteWish(wishId)
+        .accounts({
+          wish: wishPDA,
+          board: boardPDA,
+          escrow: escrowPDA,
+          stats: statsPDA,
+          profile: profilePDA,
+          wishIndex: wishIndexPDA,
+          user: payer.publicKey,
+          systemProgram,
+        })
+        .instruction()
+    );
+    expect(deleted.result).to.be.null;
+
+    // A new wish at the same address, sponsored by someone else
+    await warp(60);
+    expect((await submit()).result).to.be.null;
+    const victim = anchor.web3.Keypair.generate();
+    const funded = await send(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: payer.publicKey,
+        toPubkey: victim.publicKey,
+        lamports: anchor.web3.LAMPORTS_PER_SOL,
+      })
+    );
+    expect(funded.result).to.be.null;
+    expect((await sponsor(victim, 2_000_000)).result).to.be.null;
+
+    // The old receipt can't be refunded from the new escrow
+    const refund = await send(
+      await program.methods
+        .refundEscrow()
+        .accounts({ wish: wishPDA, escrow: escrowPDA, sponsorship: sponsorshipPDA(payer.publicKey), sponsor: payer.publicKey })
+        .instruction()
+    );
+    expect(refund.result).to.not.be.null;
+    expect(refund.meta.logMessages.join("\n")).to.include("EscrowMismatch");
+    expect((await fetchEscrow()).total.toNumber()).to.equal(2_000_000);
+
+    // Sponsoring the new wish starts the receipt over as a second sponsor
+    expect((await sponsor(payer, 500_000)).result).to.be.null;
+    const escrow = await fetchEscrow();
+    expect(escrow.total.toNumber()).to.equal(2_500_000);
+    expect(escrow.sponsors).to.equal(2);
+    const record = program.coder.accounts.decode(
+      "Sponsorship",
+      Buffer.from((await context.banksClient.getAccount(sponsorshipPDA(payer.publicKey))).data)
+    );
+    expect(record.amount.toNumber()).to.equal(500_000);
+  });
+
+  it("Takes the removed wish off the active count when an unclaimed anonymous index is reused", async () => {
+    const wishId = new anchor.BN(9);
+    const key = new anchor.web3.PublicKey(
+      createHash("sha256")
+        .update(Buffer.concat([Buffer.from("anonymous"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8)]))
+        .digest()
+    );
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), key.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const indexPDA = pda(Buffer.from("wish_index"), key.toBuffer());
+    const profilePDA = pda(Buffer.from("profile"), payer.publicKey.toBuffer());
+    const systemProgram = anchor.web3.SystemProgram.programId;
+    const activeCount = async () =>
+      program.coder.accounts.decode("UserProfile", Buffer.from((await context.banksClient.getAccount(profilePDA)).data)).activeCount;
+    const balance = async (address: anchor.web3.PublicKey) => Number(await context.banksClient.getBalance(address));
+    const submit = async () =>
+      send(
+        await program.methods
+          .submitWish(wishId, "I wish to try that again", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", true)
+          .accounts({
+            wish: wishPDA,
+            board: boardPDA,
+            stats: statsPDA,
+            leaderboard: leaderboardPDA,
+            treasury: treasuryPDA,
+            profile: profilePDA,
+            wishIndex: indexPDA,
+            user: payer.publicKey,
+            systemProgram,
+          })
+          .instruction()
+      );
+
+    await warp(60); // Past the submission cooldown
+    const active = await activeCount();
+    expect((await submit()).result).to.be.null;
+    const removed = await send(
+      await program.methods
+        .adminRemoveAnonymousWish(wishId)
+        .accounts({ wish: wishPDA, board: boardPDA, stats: statsPDA, wishIndex: indexPDA, admin: payer.publicKey })
+        .instruction()
+    );
+    expect(removed.result).to.be.null;
+    const parked = await balance(indexPDA);
+
+    // The new wish takes the parked index over without leaving the removed one counted
+    await warp(60);
+    expect((await submit()).result).to.be.null;
+    expect(await activeCount()).to.equal(active + 1);
+
+    const wishRent = await balance(wishPDA);
+    const before = await balance(payer.publicKey);
+    const deleted = await send(
+      await program.methods
+        .deleteWish(wishId)
+        .accounts({
+          wish: wishPDA,
+          board: boardPDA,
+          escrow: pda(Buffer.from("escrow"), wishPDA.toBuffer()),
+          stats: statsPDA,
+          profile: profilePDA,
+          wishIndex: indexPDA,
+          user: payer.publicKey,
+          systemProgram,
+        })
+        .instruction()
+    );
+    expect(deleted.result).to.be.null;
+    expect(await activeCount()).to.equal(active);
+    expect(await context.banksClient.getAccount(indexPDA)).to.be.null;
+    // The payer also pays the fee here, so allow for it
+    expect((await balance(payer.publicKey)) - before).to.be.greaterThan(wishRent + parked - 10_000);
+  });
+
+  it("Gives the same wish id and title distinct PDAs on different boards", async () => {
+    const goalsPDA = pda(Buffer.from("board"), createHash("sha256").update("2025 goals").digest());
+    const created = await send(
+      await program.methods
+        .createBoard("2025 goals")
+        .accounts({ board: goalsPDA, creator: payer.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .instruction()
+    );
+    expect(created.result).to.be.null;
+
+    const wishId = new anchor.BN(10);
+    const wishOn = (board: anchor.web3.PublicKey) =>
+      pda(Buffer.from("wish"), board.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const submit = async (board: anchor.web3.PublicKey) =>
+      send(
+        await program.methods
+          .submitWish(wishId, "I wish to run a half marathon", "", { other: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", false)
+          .accounts({
+            wish: wishOn(board),
+            board,
+            stats: statsPDA,
+            leaderboard: leaderboardPDA,
+            treasury: treasuryPDA,
+            profile: pda(Buffer.from("profile"), payer.publicKey.toBuffer()),
+            wishIndex: pda(Buffer.from("wish_index"), payer.publicKey.toBuffer()),
+            user: payer.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .instruction()
+      );
+
+    await warp(60); // Past the submission cooldown
+    expect((await submit(boardPDA)).result).to.be.null;
+    // The cooldown is per wallet, across every board
+    const early = await submit(goalsPDA);
+    expect(early.result).to.not.be.null;
+    expect(early.meta.logMessages.join("\n")).to.include("SubmitTooSoon");
+    await warp(60);
+    expect((await submit(goalsPDA)).result).to.be.null;
+
+    expect(wishOn(boardPDA).toBase58()).to.not.equal(wishOn(goalsPDA).toBase58());
+    for (const board of [boardPDA, goalsPDA]) {
+      const wish = program.coder.accounts.decode("AWish", Buffer.from((await context.banksClient.getAccount(wishOn(board))).data));
+      expect(wish.board.toBase58()).to.equal(board.toBase58());
+    }
+  });
+
+  it("Rejects submissions over the cap until one of the wishes is deleted", async () => {
+    const profilePDA = pda(Buffer.from("profile"), payer.publicKey.toBuffer());
+    const wishIndexPDA = pda(Buffer.from("wish_index"), payer.publicKey.toBuffer());
+    const systemProgram = anchor.web3.SystemProgram.programId;
+    const wishOf = (wishId: anchor.BN) =>
+      pda(Buffer.from("wish"), boardPDA.toBuffer(), payer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const activeCount = async () =>
+      program.coder.accounts.decode("UserProfile", Buffer.from((await context.banksClient.getAccount(profilePDA)).data)).activeCount;
+    const setWishCap = async (max: number) =>
+      send(await program.methods.setWishCap(max).accounts({ stats: statsPDA, admin: payer.publicKey }).instruction());
+    const submitMany = async (ids: number[]) =>
+      send(
+        await program.methods
+          .submitWishes(
+            ids.map((id) => ({
+              wishId: new anchor.BN(id),
+              title: `I wish for batch wish ${id}`,
+              body: "",
+              category: { other: {} },
+              visibility: { public: {} },
+              ttlSecs: new anchor.BN(0),
+              guardian: null,
+              contentHash: new Array(32).fill(0),
+              contentUri: "",
+            }))
+          )
+          .accounts({
+            board: boardPDA,
+            stats: statsPDA,
+            leaderboard: leaderboardPDA,
+            treasury: treasuryPDA,
+            profile: profilePDA,
+            wishIndex: wishIndexPDA,
+            user: payer.publicKey,
+            systemProgram,
+          })
+          .remainingAccounts(ids.map((id) => ({ pubkey: wishOf(new anchor.BN(id)), isSigner: false, isWritable: true })))
+          .instruction()
+      );
+    const expectFailure = (result: Awaited<ReturnType<typeof send>>, error: string) => {
+      expect(result.result).to.not.be.null;
+      expect(result.meta.logMessages.join("\n")).to.include(error);
+    };
+
+    await warp(60); // Past the submission cooldown
+    const active = await activeCount();
+    expect((await setWishCap(active + 2)).result).to.be.null;
+    try {
+      // A batch that would go over the cap fails as a whole
+      expectFailure(await submitMany([11, 12, 13]), "WishLimitReached");
+      expect((await submitMany([11, 12])).result).to.be.null;
+      expect(await activeCount()).to.equal(active + 2);
+
+      // Past the cooldown, the cap is what rejects the next one
+      await warp(60);
+      expectFailure(await submitMany([13]), "WishLimitReached");
+
+      const deleted = await send(
+        await program.methods
+          .deleteWish(new anchor.BN(11))
+          .accounts({
+            wish: wishOf(new anchor.BN(11)),
+            board: boardPDA,
+            escrow: pda(Buffer.from("escrow"), wishOf(new anchor.BN(11)).toBuffer()),
+            stats: statsPDA,
+            profile: profilePDA,
+            wishIndex: wishIndexPDA,
+            user: payer.publicKey,
+            systemProgram,
+          })
+          .instruction()
+      );
+      expect(deleted.result).to.be.null;
+      expect(await activeCount()).to.equal(active + 1);
+      await warp(1); // A fresh blockhash, so the retried batch isn't dropped as a duplicate
+      expect((await submitMany([13])).result).to.be.null;
+      expect(await activeCount()).to.equal(active + 2);
+    } finally {
+      expect((await setWishCap(0)).result).to.be.null;
+    }
+  });
+});
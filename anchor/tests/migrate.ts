@@ -0,0 +1,241 @@
+import * as anchor from "@project-serum/anchor";
+import { BorshAccountsCoder, Program } from "@project-serum/anchor";
+import { start, ProgramTestContext } from "solana-bankrun";
+import { WallOfWish } from "../target/types/wall_of_wish";
+import { expect } from "chai";
+import { createHash } from "crypto";
+
+// Runs the program inside bankrun so a wish in the first release's layout can be preloaded - the
+// current program can no longer create one.
+describe("wall-of-wish (legacy migration)", () => {
+  const idl = require("../target/idl/wall_of_wish.json");
+  const programId = new anchor.web3.PublicKey(idl.metadata?.address ?? "HZSqkqsgtJkFLwgyFMQHHbFEsU9jPdGZgBTpbrVRwJ8U");
+  // Only used to build instructions and decode accounts; transactions go through the bank
+  const program = new Program(idl, programId, {} as anchor.Provider) as unknown as Program<WallOfWish>;
+
+  const pda = (...seeds: Buffer[]) =>
+    anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+  const statsPDA = pda(Buffer.from("stats"));
+  const leaderboardPDA = pda(Buffer.from("leaderboard"));
+  const treasuryPDA = pda(Buffer.from("treasury"));
+  const boardPDA = pda(Buffer.from("board"), createHash("sha256").update("main").digest());
+
+  const owner = anchor.web3.Keypair.generate();
+  const legacyTitle = "I wish to survive the upgrade";
+  const legacyPDA = pda(Buffer.from("wish"), owner.publicKey.toBuffer(), Buffer.from(legacyTitle));
+  const legacyLamports = 5_000_000;
+
+  // The first release's AWish: discriminator + owner + length-prefixed title, sized exactly
+  const legacyData = () => {
+    const title = Buffer.from(legacyTitle);
+    const length = Buffer.alloc(4);
+    length.writeUInt32LE(title.length);
+    return Buffer.concat([BorshAccountsCoder.accountDiscriminator("AWish"), owner.publicKey.toBuffer(), length, title]);
+  };
+
+  let context: ProgramTestContext;
+
+  // Sign and process a single instruction, returning the bank's result instead of throwing. The
+  // bank's payer always pays the fee, so other signers' balances move only by what the program does.
+  const send = async (ix: anchor.web3.TransactionInstruction, ...signers: anchor.web3.Keypair[]) => {
+    const tx = new anchor.web3.Transaction().add(ix);
+    [tx.recentBlockhash] = await context.banksClient.getLatestBlockhash();
+    tx.feePayer = context.payer.publicKey;
+    tx.sign(context.payer, ...signers);
+    return context.banksClient.tryProcessTransaction(tx);
+  };
+
+  const migrate = async (wishId: anchor.BN, title = legacyTitle, signer = owner) =>
+    send(
+      await program.methods
+        .migrateWish(wishId, title)
+        .accounts({
+          legacyWish: pda(Buffer.from("wish"), signer.publicKey.toBuffer(), Buffer.from(title)),
+          wish: pda(Buffer.from("wish"), boardPDA.toBuffer(), signer.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8)),
+          board: boardPDA,
+          stats: statsPDA,
+          profile: pda(Buffer.from("profile"), signer.publicKey.toBuffer()),
+          wishIndex: pda(Buffer.from("wish_index"), signer.publicKey.toBuffer()),
+          user: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction(),
+      signer
+    );
+
+  before(async () => {
+    // Load the program built by `anchor build`, along with a funded owner and their legacy wish
+    process.env.SBF_OUT_DIR = "target/deploy";
+    context = await start(
+      [{ name: "wall_of_wish", programId }],
+      [
+        {
+          address: owner.publicKey,
+          info: {
+            lamports: anchor.web3.LAMPORTS_PER_SOL,
+            data: Buffer.alloc(0),
+            owner: anchor.web3.SystemProgram.programId,
+            executable: false,
+          },
+        },
+        {
+          address: legacyPDA,
+          info: { lamports: legacyLamports, data: legacyData(), owner: programId, executable: false },
+        },
+      ]
+    );
+
+    const result = await send(
+      await program.methods
+        .initializeWall()
+        .accounts({
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          authority: context.payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(result.result).to.be.null;
+
+    const board = await send(
+      await program.methods
+        .createBoard("main")
+        .accounts({
+          board: boardPDA,
+          creator: context.payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction()
+    );
+    expect(board.result).to.be.null;
+  });
+
+  it("Moves a legacy wish to its wish_id PDA and closes the old account", async () => {
+    const wishId = new anchor.BN(42);
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), owner.publicKey.toBuffer(), wishId.toArrayLike(Buffer, "le", 8));
+    const ownerBefore = await context.banksClient.getBalance(owner.publicKey);
+    const statsBefore = program.coder.accounts.decode(
+      "WallStats",
+      Buffer.from((await context.banksClient.getAccount(statsPDA)).data)
+    );
+
+    const result = await migrate(wishId);
+    expect(result.result).to.be.null;
+
+    // The old account is gone and the new one decodes in the current layout
+    expect(await context.banksClient.getAccount(legacyPDA)).to.be.null;
+    const account = await context.banksClient.getAccount(wishPDA);
+    expect(account.owner.toBase58()).to.equal(programId.toBase58());
+    const wish = program.coder.accounts.decode("AWish", Buffer.from(account.data));
+    expect(wish.user.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(wish.creator.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(wish.title).to.equal(legacyTitle);
+    expect(wish.board.toBase58()).to.equal(boardPDA.toBase58());
+    expect(wish.wishId.eq(wishId)).to.be.true;
+    expect(wish.index.eq(statsBefore.totalSubmitted)).to.be.true;
+
+    // The owner paid the rent of the new wish and of their first profile and wish index, and got
+    // the legacy lamports back
+    const ownerAfter = await context.banksClient.getBalance(owner.publicKey);
+    const profileRent = await context.banksClient.getBalance(pda(Buffer.from("profile"), owner.publicKey.toBuffer()));
+    const indexRent = await context.banksClient.getBalance(pda(Buffer.from("wish_index"), owner.publicKey.toBuffer()));
+    expect(ownerAfter - ownerBefore).to.equal(
+      BigInt(legacyLamports) - BigInt(account.lamports) - profileRent - indexRent
+    );
+
+    // The migrated wish is listed in the owner's index
+    const index = program.coder.accounts.decode(
+      "WishIndex",
+      Buffer.from((await context.banksClient.getAccount(pda(Buffer.from("wish_index"), owner.publicKey.toBuffer()))).data)
+    );
+    expect(index.wishes.map((key) => key.toBase58())).to.deep.equal([wishPDA.toBase58()]);
+  });
+
+  it("Can't migrate the same legacy wish twice", async () => {
+    const result = await migrate(new anchor.BN(43));
+    expect(result.result).to.not.be.null;
+    expect(result.meta.logMessages.join("\n")).to.include("NotALegacyWish");
+  });
+
+  it("Rejects a wish that is already in the current layout", async () => {
+    // Wish PDAs now include the board, so a current wish can't sit at a legacy address by itself -
+    // copy the one just migrated to id 42 to the legacy address of another title
+    const title = "I wish to look current";
+    const current = await context.banksClient.getAccount(
+      pda(Buffer.from("wish"), boardPDA.toBuffer(), owner.publicKey.toBuffer(), new anchor.BN(42).toArrayLike(Buffer, "le", 8))
+    );
+    context.setAccount(pda(Buffer.from("wish"), owner.publicKey.toBuffer(), Buffer.from(title)), {
+      lamports: current.lamports,
+      data: Buffer.from(current.data),
+      owner: programId,
+      executable: false,
+    });
+
+    const result = await migrate(new anchor.BN(44), title);
+    expect(result.result).to.not.be.null;
+    expect(result.meta.logMessages.join("\n")).to.include("NotALegacyWish");
+  });
+
+  describe("versioned layouts", () => {
+    const wishPDA = pda(Buffer.from("wish"), boardPDA.toBuffer(), owner.publicKey.toBuffer(), new anchor.BN(42).toArrayLike(Buffer, "le", 8));
+
+    const migrateAccount = async (signer = owner) =>
+      send(
+        await program.methods
+          .migrateAccount()
+          .accounts({ wish: wishPDA, user: signer.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+          .instruction(),
+        signer
+      );
+
+    it("Upgrades a v1 wish in place, keeping its owner and title", async () => {
+      // A v1 wish is the current layout without the version byte after the discriminator
+      const current = await context.banksClient.getAccount(wishPDA);
+      const before = program.coder.accounts.decode("AWish", Buffer.from(current.data));
+      const v1 = Buffer.concat([Buffer.from(current.data.subarray(0, 8)), Buffer.from(current.data.subarray(9))]);
+      const rent = await context.banksClient.getRent();
+      context.setAccount(wishPDA, {
+        lamports: Number(rent.minimumBalance(BigInt(v1.length))),
+        data: v1,
+        owner: programId,
+        executable: false,
+      });
+
+      // Only the owner can upgrade it
+      const stranger = await migrateAccount(context.payer);
+      expect(stranger.result).to.not.be.null;
+      expect(stranger.meta.logMessages.join("\n")).to.include("UnauthorizedUpdate");
+
+      const ownerBefore = await context.banksClient.getBalance(owner.publicKey);
+      const result = await migrateAccount();
+      expect(result.result).to.be.null;
+
+      const account = await context.banksClient.getAccount(wishPDA);
+      expect(account.data.length).to.equal(v1.length + 1);
+      const wish = program.coder.accounts.decode("AWish", Buffer.from(account.data));
+      expect(wish.version).to.equal(2);
+      expect(wish.user.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(wish.title).to.equal(legacyTitle);
+      expect(wish.wishId.eq(before.wishId)).to.be.true;
+      expect(wish.index.eq(before.index)).to.be.true;
+      expect(wish.board.toBase58()).to.equal(before.board.toBase58());
+
+      // The owner paid for the extra byte
+      const ownerAfter = await context.banksClient.getBalance(owner.publicKey);
+      expect(ownerBefore - ownerAfter).to.equal(
+        rent.minimumBalance(BigInt(v1.length + 1)) - rent.minimumBalance(BigInt(v1.length))
+      );
+    });
+
+    it("Rejects a wish that is already current", async () => {
+      // Advance a slot so the retry gets a fresh blockhash instead of being dropped as a duplicate
+      const { slot } = await context.banksClient.getClock();
+      context.warpToSlot(slot + BigInt(1));
+      const result = await migrateAccount();
+      expect(result.result).to.not.be.null;
+      expect(result.meta.logMessages.join("\n")).to.include("WishUpToDate");
+    });
+  });
+});
@@ -2,6 +2,7 @@ import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
 import { WallOfWish } from "../target/types/wall_of_wish";
 import { expect } from "chai";
+import { createHash } from "crypto";
 
 describe("wall-of-wish", () => {
   // Configure the client to use the local cluster
@@ -11,35 +12,3137 @@ describe("wall-of-wish", () => {
   const program = anchor.workspace.WallOfWish as Program<WallOfWish>;
   const user = provider.wallet;
 
-  it("Can submit a wish", async () => {
-    // Data for our test
-    const wishTitle = "I wish to learn Solana";
-    
-    // Find the PDA for this wish
+  // Singleton stats PDA for the wall
+  const [statsPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("stats")],
+    program.programId
+  );
+
+  // Singleton leaderboard PDA, created alongside the stats
+  const [leaderboardPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("leaderboard")],
+    program.programId
+  );
+
+  // Singleton treasury PDA that collects submission fees
+  const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+
+  // Board PDAs are seeded by the SHA-256 of the board's name
+  const findBoardPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("board"), createHash("sha256").update(name).digest()],
+      program.programId
+    )[0];
+
+  // Open a board, signed by its creator (the provider wallet by default)
+  const createBoard = async (name: string, creator?: anchor.web3.Keypair) => {
+    const board = findBoardPDA(name);
+    await program.methods
+      .createBoard(name)
+      .accounts({
+        board,
+        creator: creator ? creator.publicKey : user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers(creator ? [creator] : [])
+      .rpc();
+    return board;
+  };
+
+  // Board most tests submit to, created alongside the wall
+  const mainBoardPDA = findBoardPDA("main");
+
+  before(async () => {
+    await program.methods
+      .initializeWall()
+      .accounts({
+        stats: statsPDA,
+        leaderboard: leaderboardPDA,
+        treasury: treasuryPDA,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await createBoard("main");
+  });
+
+  // Find the PDA for a wish, keyed by its board, creator and wish id
+  const findWishPDA = async (
+    creator: anchor.web3.PublicKey,
+    wishId: anchor.BN,
+    board = mainBoardPDA
+  ) => {
     const [wishPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
       [
         Buffer.from("wish"),
-        user.publicKey.toBuffer(),
-        Buffer.from(wishTitle),
+        board.toBuffer(),
+        creator.toBuffer(),
+        wishId.toArrayLike(Buffer, "le", 8),
       ],
       program.programId
     );
+    return wishPDA;
+  };
+
+  // Find the per-user profile PDA that tracks the submission cooldown
+  const findProfilePDA = async (owner: anchor.web3.PublicKey) => {
+    const [profilePDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("profile"), owner.toBuffer()],
+      program.programId
+    );
+    return profilePDA;
+  };
+
+  // Find the per-user index PDA listing the wishes a wallet owns
+  const findWishIndexPDA = async (owner: anchor.web3.PublicKey) => {
+    const [indexPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("wish_index"), owner.toBuffer()],
+      program.programId
+    );
+    return indexPDA;
+  };
+
+  // Find the escrow PDA holding a wish's sponsorships
+  const findEscrowPDA = async (wishPDA: anchor.web3.PublicKey) => {
+    const [escrowPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), wishPDA.toBuffer()],
+      program.programId
+    );
+    return escrowPDA;
+  };
+
+  // Hand out a fresh wish id per submission so tests don't collide
+  let nextWishId = 100;
+  const newWishId = () => new anchor.BN(nextWishId++);
+
+  // Create a new keypair funded with an airdrop
+  const newUser = async () => {
+    const keypair = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    return keypair;
+  };
+
+  type SubmitOptions = {
+    body?: string;
+    category?: object;
+    visibility?: object;
+    ttlSecs?: number;
+    guardian?: anchor.web3.PublicKey;
+    wishId?: anchor.BN;
+    contentHash?: number[];
+    contentUri?: string;
+    owner?: anchor.web3.Keypair;
+    board?: anchor.web3.PublicKey;
+    anonymous?: boolean;
+  };
+
+  // Key an anonymous wish is filed under instead of its submitter: sha256("anonymous" || board ||
+  // submitter || wish id)
+  const anonymousOwner = (board: anchor.web3.PublicKey, submitter: anchor.web3.PublicKey, wishId: anchor.BN) =>
+    new anchor.web3.PublicKey(
+      createHash("sha256")
+        .update(Buffer.concat([Buffer.from("anonymous"), board.toBuffer(), submitter.toBuffer(), wishId.toArrayLike(Buffer, "le", 8)]))
+        .digest()
+    );
+
+  // Submit a wish and return its id, PDA, owner and board. Each submission comes from a fresh
+  // wallet by default so the per-user cooldown doesn't get in the way.
+  const submitWish = async (title: string, options: SubmitOptions = {}) => {
+    const owner = options.owner ?? (await newUser());
+    const wishId = options.wishId ?? newWishId();
+    const board = options.board ?? mainBoardPDA;
+    // An anonymous wish's PDA and index are seeded by its anonymous key; the profile is still the owner's
+    const key = options.anonymous ? anonymousOwner(board, owner.publicKey, wishId) : owner.publicKey;
+    const wishPDA = await findWishPDA(key, wishId, board);
+    await program.methods
+      .submitWish(
+        wishId,
+        title,
+        options.body ?? "",
+        options.category ?? { other: {} },
+        options.visibility ?? { public: {} },
+        new anchor.BN(options.ttlSecs ?? 0),
+        options.guardian ?? null,
+        options.contentHash ?? new Array(32).fill(0),
+        options.contentUri ?? "",
+        options.anonymous ?? false
+      )
+      .accounts({
+        wish: wishPDA,
+        board,
+        stats: statsPDA,
+        leaderboard: leaderboardPDA,
+        treasury: treasuryPDA,
+        profile: await findProfilePDA(owner.publicKey),
+        wishIndex: await findWishIndexPDA(key),
+        user: owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    return { wishId, wishPDA, owner, board };
+  };
+
+  type SubmittedWish = Awaited<ReturnType<typeof submitWish>>;
+
+  // Submit `count` wishes for the owner in a single batch, skipping the cooldown between them.
+  // `overrides[i]` sets the title, body or guardian of the i-th wish.
+  const submitMany = async (
+    owner: anchor.web3.Keypair,
+    count: number,
+    overrides: { title?: string; body?: string; guardian?: anchor.web3.PublicKey }[] = []
+  ) => {
+    const entries = [];
+    const wishPDAs = [];
+    for (let i = 0; i < count; i++) {
+      const wishId = newWishId();
+      entries.push({
+        wishId,
+        title: overrides[i]?.title ?? `Batch wish ${i}`,
+        body: overrides[i]?.body ?? "",
+        category: { other: {} },
+        visibility: { public: {} },
+        ttlSecs: new anchor.BN(0),
+        guardian: overrides[i]?.guardian ?? null,
+        contentHash: new Array(32).fill(0),
+        contentUri: "",
+      });
+      wishPDAs.push(await findWishPDA(owner.publicKey, wishId));
+    }
+    await program.methods
+      .submitWishes(entries)
+      .accounts({
+        board: mainBoardPDA,
+        stats: statsPDA,
+        leaderboard: leaderboardPDA,
+        treasury: treasuryPDA,
+        profile: await findProfilePDA(owner.publicKey),
+        wishIndex: await findWishIndexPDA(owner.publicKey),
+        user: owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(
+        wishPDAs.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .signers([owner])
+      .rpc();
+    return wishPDAs.map((wishPDA, i) => ({ wishId: entries[i].wishId, wishPDA, owner, board: mainBoardPDA }));
+  };
+
+  // Delete a wish as its owner
+  const deleteWish = async (wish: SubmittedWish) =>
+    program.methods
+      .deleteWish(wish.wishId)
+      .accounts({
+        wish: wish.wishPDA,
+        board: wish.board,
+        escrow: await findEscrowPDA(wish.wishPDA),
+        stats: statsPDA,
+        profile: await findProfilePDA(wish.owner.publicKey),
+        wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+        user: wish.owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([wish.owner])
+      .rpc();
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  // Assert that a transaction fails with the given program error code
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code} error`);
+  };
+
+  // Run an instruction and resolve with the first matching event it emits
+  const captureEvent = async (name: string, run: () => Promise<unknown>) => {
+    let listener: number;
+    const event = new Promise<any>((resolve) => {
+      listener = program.addEventListener(name, (event) => resolve(event));
+    });
+    await run();
+    const result = await event;
+    await program.removeEventListener(listener);
+    return result;
+  };
+
+  // Account size for a wish: discriminator + version + user + wish id + created_at + likes + fulfilled + category
+  // + creator + total tipped + comment count + reports + visibility + expires_at + followers + last_edited_at
+  // + index + reaction counts + content hash + pinned_until + claimed_at + archived + sealed + board
+  // + weighted likes + delete_requested_at + optional guardian + optional claimer + optional granter + string prefix + title bytes
+  // + string prefix + body bytes + string prefix + content URI bytes
+  const wishSpace = (title: string, body = "", contentUri = "") =>
+    8 + 1 + 32 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 2 + 1 + 8 + 4 + 8 + 8 + 16 + 32 + 8 + 8 + 1 + 1 + 32 + 8 + 8 + 33 + 33 + 33 + 4 +
+    Buffer.byteLength(title) +
+    4 + Buffer.byteLength(body) + 4 + Buffer.byteLength(contentUri);
+
+  // Account size for a user profile: discriminator + last submit + wish count + active count
+  // + activity total + ACTIVITY_LOG_SIZE (8) activity entries of kind + timestamp + target
+  // + first seen + tips given
+  const profileSpace = 8 + 8 + 4 + 4 + 8 + 8 * (1 + 8 + 32) + 8 + 8;
+
+  // Account size for a wish index with room for `capacity` wishes: discriminator + user + vec length
+  // + wish keys. It grows and shrinks in chunks of WISH_INDEX_CHUNK (4) slots.
+  const wishIndexSpace = (capacity: number) => 8 + 32 + 4 + 32 * capacity;
+
+  it("Initializes the wall under the declared program ID", async () => {
+    // The before hook's initialize_wall only succeeds when the deployed ID matches declare_id! -
+    // otherwise Anchor rejects it with DeclaredProgramIdMismatch (4100)
+    const idl = require("../target/idl/wall_of_wish.json");
+    expect(program.programId.toBase58()).to.equal(
+      idl.metadata?.address ?? "HZSqkqsgtJkFLwgyFMQHHbFEsU9jPdGZgBTpbrVRwJ8U"
+    );
+
+    const stats = await program.account.wallStats.fetch(statsPDA);
+    expect(stats.admin.toBase58()).to.equal(user.publicKey.toBase58());
+    expect(stats.paused).to.be.false;
+  });
+
+  it("Can submit a wish", async () => {
+    // Data for our test
+    const wishId = new anchor.BN(1);
+    const wishTitle = "I wish to learn Solana";
+    const wishPDA = await findWishPDA(user.publicKey, wishId);
 
     // Submit the wish
     await program.methods
-      .submitWish(wishTitle)
+      .submitWish(wishId, wishTitle, "", { learning: {} }, { public: {} }, new anchor.BN(0), null, new Array(32).fill(0), "", false)
       .accounts({
         wish: wishPDA,
+        board: mainBoardPDA,
+        stats: statsPDA,
+        leaderboard: leaderboardPDA,
+        treasury: treasuryPDA,
+        profile: await findProfilePDA(user.publicKey),
+        wishIndex: await findWishIndexPDA(user.publicKey),
         user: user.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
 
     // Fetch the created wish account
-    const wishAccount = await program.account.wish.fetch(wishPDA);
-    
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+
     // Verify the account data
     expect(wishAccount.title).to.equal(wishTitle);
     expect(wishAccount.user.toString()).to.equal(user.publicKey.toString());
+    expect(wishAccount.wishId.toNumber()).to.equal(wishId.toNumber());
+  });
+
+  it("Can update a wish title and settles the rent difference", async () => {
+    const connection = provider.connection;
+    const { wishId, wishPDA, owner } = await submitWish("A short wish");
+
+    // Update the title and check the account and the owner's balance moved by the rent delta.
+    // The provider wallet pays the fee, so the owner's balance only reflects rent.
+    const updateTitle = async (newTitle: string) => {
+      const wishBefore = await connection.getBalance(wishPDA);
+      const ownerBefore = await connection.getBalance(owner.publicKey);
+
+      await program.methods
+        .updateWish(wishId, newTitle, "")
+        .accounts({
+          wish: wishPDA,
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+      const expectedRent = await connection.getMinimumBalanceForRentExemption(
+        wishSpace(newTitle)
+      );
+      const wishAfter = await connection.getBalance(wishPDA);
+      const ownerAfter = await connection.getBalance(owner.publicKey);
+
+      expect(wishAfter).to.equal(expectedRent);
+      expect(ownerBefore - ownerAfter).to.equal(wishAfter - wishBefore);
+
+      const wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.title).to.equal(newTitle);
+      return wishAfter - wishBefore;
+    };
+
+    // Growing the title charges the user extra rent
+    const grown = await updateTitle("A much longer wish that needs a bigger account");
+    expect(grown).to.be.greaterThan(0);
+
+    // Shrinking the title refunds the difference to the user
+    const shrunk = await updateTitle("Tiny");
+    expect(shrunk).to.be.lessThan(0);
+  });
+
+  it("Shrinks to the exact size on edit without truncating the body", async () => {
+    const body = "Every weekend, rain or shine, until the whole trail is done.";
+    const { wishId, wishPDA, owner } = await submitWish("Hike", { body });
+    const update = (title: string) =>
+      program.methods
+        .updateWish(wishId, title, body)
+        .accounts({
+          wish: wishPDA,
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+    await update("Hike the entire Appalachian Trail from Georgia to Maine");
+    const ownerBefore = await provider.connection.getBalance(owner.publicKey);
+    await update("Hike");
+    const ownerAfter = await provider.connection.getBalance(owner.publicKey);
+
+    // update_wish already reallocs down, so there is no slack left to compact
+    const accountInfo = await provider.connection.getAccountInfo(wishPDA);
+    expect(accountInfo.data.length).to.equal(wishSpace("Hike", body));
+    expect(ownerAfter).to.be.greaterThan(ownerBefore);
+
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.title).to.equal("Hike");
+    expect(wishAccount.body).to.equal(body);
+  });
+
+  it("Creates wish accounts that are rent-exempt", async () => {
+    const { wishPDA } = await submitWish("I wish to stay on-chain", { body: "for as long as I like" });
+    const accountInfo = await provider.connection.getAccountInfo(wishPDA);
+    const minimum = await provider.connection.getMinimumBalanceForRentExemption(accountInfo.data.length);
+    expect(accountInfo.lamports).to.be.at.least(minimum);
+    expect(accountInfo.data.length).to.equal(wishSpace("I wish to stay on-chain", "for as long as I like"));
+  });
+
+  it("Stores a short title alongside a longer body", async () => {
+    const title = "Run a marathon";
+    const body = "I want to train all year and finish a full marathon in under four hours.";
+    const { wishPDA } = await submitWish(title, { body });
+
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.title).to.equal(title);
+    expect(wishAccount.body).to.equal(body);
+
+    const accountInfo = await provider.connection.getAccountInfo(wishPDA);
+    expect(accountInfo.data.length).to.equal(wishSpace(title, body));
+  });
+
+  it("Edits only the body without moving the wish", async () => {
+    const title = "Learn to paint";
+    const { wishId, wishPDA, owner } = await submitWish(title, { body: "Watercolours" });
+    const newBody = "Oils, watercolours and eventually a gallery show";
+
+    await program.methods
+      .updateWish(wishId, title, newBody)
+      .accounts({
+        wish: wishPDA,
+        user: owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.title).to.equal(title);
+    expect(wishAccount.body).to.equal(newBody);
+
+    const accountInfo = await provider.connection.getAccountInfo(wishPDA);
+    expect(accountInfo.data.length).to.equal(wishSpace(title, newBody));
+  });
+
+  it("Stamps last_edited_at on edit but not on submit", async () => {
+    const title = "Run a marathon";
+    const { wishId, wishPDA, owner } = await submitWish(title);
+    expect((await program.account.aWish.fetch(wishPDA)).lastEditedAt.toNumber()).to.equal(0);
+
+    await program.methods
+      .updateWish(wishId, "Run two marathons", "")
+      .accounts({
+        wish: wishPDA,
+        user: owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.lastEditedAt.toNumber()).to.be.at.least(wishAccount.createdAt.toNumber());
+  });
+
+  describe("off-chain content", () => {
+    const updateContent = (wish: SubmittedWish, signer: anchor.web3.Keypair, hash: number[], uri: string) =>
+      program.methods
+        .updateContent(wish.wishId, hash, uri)
+        .accounts({
+          wish: wish.wishPDA,
+          user: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("Round-trips the content hash and lets only the owner repoint it", async () => {
+      const title = "I wish to publish my photo book";
+      const hash = Array.from({ length: 32 }, (_, i) => i);
+      const wish = await submitWish(title, { contentHash: hash, contentUri: "ipfs://bafyoriginal" });
+
+      let wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(Array.from(wishAccount.contentHash)).to.deep.equal(hash);
+      expect(wishAccount.contentUri).to.equal("ipfs://bafyoriginal");
+
+      const newHash = new Array(32).fill(7);
+      const newUri = "ar://a-much-longer-arweave-transaction-identifier";
+      await expectError(updateContent(wish, await newUser(), newHash, newUri), "UnauthorizedUpdate");
+
+      await updateContent(wish, wish.owner, newHash, newUri);
+      wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(Array.from(wishAccount.contentHash)).to.deep.equal(newHash);
+      expect(wishAccount.contentUri).to.equal(newUri);
+      expect(wishAccount.title).to.equal(title);
+
+      const accountInfo = await provider.connection.getAccountInfo(wish.wishPDA);
+      expect(accountInfo.data.length).to.equal(wishSpace(title, "", newUri));
+    });
+
+    it("Rejects a content URI over the maximum length", async () => {
+      await expectError(
+        submitWish("I wish for a very long link", { contentUri: "a".repeat(129) }),
+        "ContentUriTooLong"
+      );
+    });
+  });
+
+  it("Rejects a body over the maximum length", async () => {
+    await expectError(
+      submitWish("Too much to say", { body: "a".repeat(513) }),
+      "BodyTooLong"
+    );
+  });
+
+  it("Accepts a title at exactly the maximum length", async () => {
+    const title = "a".repeat(280);
+    const { wishPDA } = await submitWish(title);
+
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.title).to.equal(title);
+  });
+
+  it("Rejects a title one character over the maximum length", async () => {
+    await expectError(submitWish("a".repeat(281)), "TitleTooManyChars");
+  });
+
+  it("Limits characters and bytes separately for multi-byte titles", async () => {
+    // 280 two-byte characters: at both limits at once
+    const accented = "\u00e9".repeat(280);
+    expect(Buffer.byteLength(accented)).to.equal(560);
+    const { wishPDA } = await submitWish(accented);
+    expect((await program.account.aWish.fetch(wishPDA)).title).to.equal(accented);
+
+    // 139 four-byte emoji plus three ASCII characters: well under the character limit but 1 byte
+    // short of the byte limit, and the account is sized by the bytes
+    const emoji = "\u{1F320}".repeat(139) + "!!!";
+    expect(Buffer.byteLength(emoji)).to.equal(559);
+    const starry = await submitWish(emoji);
+    const info = await provider.connection.getAccountInfo(starry.wishPDA);
+    expect(info.data.length).to.equal(wishSpace(emoji));
+
+    // One more emoji goes over the byte limit while still only 143 characters long
+    await expectError(submitWish("\u{1F320}".repeat(140) + "!!!"), "TitleTooLong");
+  });
+
+  it("Rejects an empty title", async () => {
+    await expectError(submitWish(""), "TitleEmpty");
+  });
+
+  it("Rejects an all-whitespace title", async () => {
+    await expectError(submitWish("   "), "TitleEmpty");
+    await expectError(submitWish(" \u3000 "), "TitleEmpty"); // Unicode whitespace counts too
+  });
+
+  it("Rejects a title containing control characters", async () => {
+    await expectError(submitWish("I wish\nfor a line break"), "InvalidCharacters");
+    await expectError(submitWish("I wish\u0000for a NUL"), "InvalidCharacters");
+  });
+
+  it("Accepts a title with ordinary punctuation and emoji", async () => {
+    const title = "  I wish to see the aurora, finally! \u{1F30C}";
+    const { wishPDA } = await submitWish(title);
+    expect((await program.account.aWish.fetch(wishPDA)).title).to.equal(title);
+  });
+
+  it("Applies the same title checks to edits", async () => {
+    const { wishPDA, wishId, owner } = await submitWish("I wish to keep a tidy title");
+    const update = (newTitle: string) =>
+      program.methods
+        .updateWish(wishId, newTitle, "")
+        .accounts({
+          wish: wishPDA,
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+    await expectError(update("\t"), "TitleEmpty");
+    await expectError(update("I wish\u0007 to ring a bell"), "InvalidCharacters");
+  });
+
+  it("Rejects a reused wish id from the same user but not the same title from another", async () => {
+    const title = "I wish to climb Kilimanjaro";
+    const first = await submitWish(title);
+
+    await expectError(
+      submitWish("I wish to climb it again", { owner: first.owner, wishId: first.wishId }),
+      "DuplicateWish"
+    );
+
+    // Seeds include the creator's key, so another user's identical wish lives at its own address
+    const second = await submitWish(title, { wishId: first.wishId });
+    expect(second.wishPDA.toString()).to.not.equal(first.wishPDA.toString());
+    expect((await program.account.aWish.fetch(second.wishPDA)).title).to.equal(title);
+  });
+
+  it("Submits to a wish PDA that someone funded in advance", async () => {
+    const owner = await newUser();
+    const wishId = newWishId();
+    const wishPDA = await findWishPDA(owner.publicKey, wishId);
+    // Enough for the address to exist as a system account, but short of a wish's rent
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: user.publicKey, toPubkey: wishPDA, lamports: 1_000_000 })
+      )
+    );
+
+    const wish = await submitWish("I wish to beat the squatters", { owner, wishId });
+    const account = await provider.connection.getAccountInfo(wish.wishPDA);
+    expect(account.owner.toBase58()).to.equal(program.programId.toBase58());
+    expect(account.data.length).to.equal(wishSpace("I wish to beat the squatters"));
+    expect(account.lamports).to.equal(await provider.connection.getMinimumBalanceForRentExemption(account.data.length));
+    expect((await program.account.aWish.fetch(wish.wishPDA)).user.toBase58()).to.equal(owner.publicKey.toBase58());
+  });
+
+  it("Returns stable numeric error codes for invalid titles", async () => {
+    // Numbers documented in the README's error table, for clients that branch on the code
+    const errorNumber = async (promise: Promise<unknown>) => {
+      try {
+        await promise;
+      } catch (err) {
+        return err.error.errorCode.number;
+      }
+      expect.fail("expected an error");
+    };
+    expect(await errorNumber(submitWish("a".repeat(561)))).to.equal(6019); // TitleTooLong
+    expect(await errorNumber(submitWish(""))).to.equal(6020); // TitleEmpty
+    expect(await errorNumber(submitWish("Fine", { body: "a".repeat(513) }))).to.equal(6021); // BodyTooLong
+  });
+
+  it("Derives valid, distinct PDAs regardless of title length", async () => {
+    // Titles aren't seeds, so a title longer than the 32-byte seed limit must still work
+    const short = await submitWish("a".repeat(5));
+    const long = await submitWish("b".repeat(200));
+
+    expect(short.wishPDA.toString()).to.not.equal(long.wishPDA.toString());
+    for (const wish of [short, long]) {
+      expect(anchor.web3.PublicKey.isOnCurve(wish.wishPDA.toBuffer())).to.be.false;
+      const info = await provider.connection.getAccountInfo(wish.wishPDA);
+      expect(info.owner.toString()).to.equal(program.programId.toString());
+    }
+    expect((await program.account.aWish.fetch(long.wishPDA)).title).to.equal("b".repeat(200));
+  });
+
+  it("Emits events with the wish PDA on submit and delete", async () => {
+    const title = "I wish for live updates";
+    const owner = await newUser();
+    let wish: SubmittedWish;
+
+    const submitted = await captureEvent("WishSubmitted", async () => {
+      wish = await submitWish(title, { owner });
+    });
+    expect(submitted.user.toString()).to.equal(owner.publicKey.toString());
+    expect(submitted.title).to.equal(title);
+    expect(submitted.wish.toString()).to.equal(wish.wishPDA.toString());
+
+    const deleted = await captureEvent("WishDeleted", () => deleteWish(wish));
+    expect(deleted.user.toString()).to.equal(owner.publicKey.toString());
+    expect(deleted.wish.toString()).to.equal(wish.wishPDA.toString());
+    expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+  });
+
+  it("Fully closes a deleted wish so it no longer deserializes", async () => {
+    const wish = await submitWish("I wish to vanish without a trace");
+    const rent = await provider.connection.getBalance(wish.wishPDA);
+    const ownerBefore = await provider.connection.getBalance(wish.owner.publicKey);
+
+    await deleteWish(wish);
+
+    // Every lamport went back to the owner, leaving no dust behind
+    const ownerAfter = await provider.connection.getBalance(wish.owner.publicKey);
+    expect(ownerAfter - ownerBefore).to.equal(rent);
+    expect(await provider.connection.getBalance(wish.wishPDA)).to.equal(0);
+
+    // With no data (not even the discriminator) there is nothing left to deserialize
+    expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+    expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.be.null;
+
+    let failed = false;
+    try {
+      await program.account.aWish.fetch(wish.wishPDA);
+    } catch (_err) {
+      failed = true;
+    }
+    expect(failed).to.be.true;
+  });
+
+  it("Stamps created_at from the on-chain clock", async () => {
+    const { wishPDA } = await submitWish("I wish to remember when I wished");
+
+    const slot = await provider.connection.getSlot("confirmed");
+    const blockTime = await provider.connection.getBlockTime(slot);
+    const wishAccount = await program.account.aWish.fetch(wishPDA);
+
+    expect(wishAccount.createdAt.toNumber()).to.be.greaterThan(0);
+    expect(Math.abs(blockTime - wishAccount.createdAt.toNumber())).to.be.lessThan(30);
+  });
+
+  it("Only the creator can mark a wish fulfilled, and only once", async () => {
+    const { wishId, wishPDA, owner } = await submitWish("I wish to be granted");
+    const stranger = await newUser();
+
+    let wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.fulfilled).to.be.false;
+
+    await expectError(
+      program.methods
+        .markFulfilled(wishId)
+        .accounts({ wish: wishPDA, user: stranger.publicKey })
+        .signers([stranger])
+        .rpc(),
+      "UnauthorizedFulfillment"
+    );
+
+    await program.methods
+      .markFulfilled(wishId)
+      .accounts({ wish: wishPDA, user: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    wishAccount = await program.account.aWish.fetch(wishPDA);
+    expect(wishAccount.fulfilled).to.be.true;
+
+    await expectError(
+      program.methods
+        .markFulfilled(wishId)
+        .accounts({ wish: wishPDA, user: owner.publicKey })
+        .signers([owner])
+        .rpc(),
+      "AlreadyFulfilled"
+    );
+  });
+
+  describe("batch submission", () => {
+    // Submit a batch with the given wish PDAs as remaining accounts
+    const submitWishes = async (
+      owner: anchor.web3.Keypair,
+      entries: { wishId: anchor.BN; title: string }[],
+      wishPDAs: anchor.web3.PublicKey[]
+    ) =>
+      program.methods
+        .submitWishes(
+          entries.map((entry) => ({
+            ...entry,
+            body: "",
+            category: { other: {} },
+            visibility: { public: {} },
+            ttlSecs: new anchor.BN(0),
+            guardian: null,
+            contentHash: new Array(32).fill(0),
+            contentUri: "",
+          }))
+        )
+        .accounts({
+          board: mainBoardPDA,
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          profile: await findProfilePDA(owner.publicKey),
+          wishIndex: await findWishIndexPDA(owner.publicKey),
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          wishPDAs.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([owner])
+        .rpc();
+
+    it("Submits three wishes in one transaction", async () => {
+      const owner = await newUser();
+      const entries = ["Batch wish one", "Batch wish two", "Batch wish three"].map((title) => ({
+        wishId: newWishId(),
+        title,
+      }));
+      const pdas = await Promise.all(entries.map((e) => findWishPDA(owner.publicKey, e.wishId)));
+
+      await submitWishes(owner, entries, pdas);
+
+      for (let i = 0; i < entries.length; i++) {
+        const wishAccount = await program.account.aWish.fetch(pdas[i]);
+        expect(wishAccount.title).to.equal(entries[i].title);
+        expect(wishAccount.user.toString()).to.equal(owner.publicKey.toString());
+      }
+
+      const profile = await program.account.userProfile.fetch(
+        await findProfilePDA(owner.publicKey)
+      );
+      expect(profile.wishCount).to.equal(3);
+    });
+
+    it("Rejects a batch where two entries collide on the same PDA", async () => {
+      const owner = await newUser();
+      const wishId = newWishId();
+      const pda = await findWishPDA(owner.publicKey, wishId);
+
+      await expectError(
+        submitWishes(
+          owner,
+          [
+            { wishId, title: "Same slot" },
+            { wishId, title: "Same slot again" },
+          ],
+          [pda, pda]
+        ),
+        "DuplicateWishInBatch"
+      );
+    });
+
+    it("Rejects a batch whose account count doesn't match the titles", async () => {
+      const owner = await newUser();
+      const entries = [
+        { wishId: newWishId(), title: "Has an account" },
+        { wishId: newWishId(), title: "Has no account" },
+      ];
+      const pda = await findWishPDA(owner.publicKey, entries[0].wishId);
+
+      await expectError(submitWishes(owner, entries, [pda]), "AccountMismatch");
+    });
+
+    it("Rejects a batch over MAX_SUBMIT_BATCH (8) before doing any work", async () => {
+      // Empty titles and one repeated account keep the transaction small; the size check comes
+      // first, so neither gets as far as being rejected on its own
+      const owner = await newUser();
+      const entries = Array.from({ length: 9 }, () => ({ wishId: newWishId(), title: "" }));
+      const pda = await findWishPDA(owner.publicKey, entries[0].wishId);
+
+      await expectError(submitWishes(owner, entries, entries.map(() => pda)), "BatchTooLarge");
+    });
+  });
+
+  it("Tracks total and active wish counts on the wall", async () => {
+    const before = await program.account.wallStats.fetch(statsPDA);
+
+    const wishes = [];
+    for (const title of ["First counted wish", "Second counted wish", "Third counted wish"]) {
+      wishes.push(await submitWish(title));
+    }
+    await deleteWish(wishes[0]);
+
+    const after = await program.account.wallStats.fetch(statsPDA);
+    expect(after.totalSubmitted.sub(before.totalSubmitted).toNumber()).to.equal(3);
+    expect(after.active.sub(before.active).toNumber()).to.equal(2);
+  });
+
+  it("Numbers wishes consecutively from the wall counter", async () => {
+    const before = await program.account.wallStats.fetch(statsPDA);
+    const first = before.totalSubmitted.toNumber();
+
+    const wishes = [];
+    for (const title of ["First paged wish", "Second paged wish", "Third paged wish"]) {
+      wishes.push(await submitWish(title));
+    }
+    const indices = [];
+    for (const wish of wishes) {
+      indices.push((await program.account.aWish.fetch(wish.wishPDA)).index.toNumber());
+    }
+    expect(indices).to.deep.equal([first, first + 1, first + 2]);
+
+    // The index sits at a fixed offset, so a page can be fetched with a memcmp on it
+    const page = await program.account.aWish.all([
+      {
+        memcmp: {
+          offset: 138,
+          bytes: anchor.utils.bytes.bs58.encode(new anchor.BN(first + 1).toArrayLike(Buffer, "le", 8)),
+        },
+      },
+    ]);
+    expect(page.map((w) => w.publicKey.toString())).to.deep.equal([wishes[1].wishPDA.toString()]);
+  });
+
+  it("Stores a category and supports memcmp filtering on it", async () => {
+    const travel = await submitWish("I wish to see the northern lights", { category: { travel: {} } });
+    const career = await submitWish("I wish to ship my first dapp", { category: { career: {} } });
+
+    const travelAccount = await program.account.aWish.fetch(travel.wishPDA);
+    const careerAccount = await program.account.aWish.fetch(career.wishPDA);
+    expect(travelAccount.category).to.deep.equal({ travel: {} });
+    expect(careerAccount.category).to.deep.equal({ career: {} });
+
+    // Category sits at a fixed offset, so indexers can filter with a memcmp on its discriminant byte
+    const travelWishes = await program.account.aWish.all([
+      {
+        memcmp: {
+          offset: 66,
+          bytes: anchor.utils.bytes.bs58.encode(Buffer.from([0])),
+        },
+      },
+    ]);
+    const travelKeys = travelWishes.map((w) => w.publicKey.toString());
+    expect(travelKeys).to.include(travel.wishPDA.toString());
+    expect(travelKeys).to.not.include(career.wishPDA.toString());
+  });
+
+  it("Enforces a cooldown between submissions from the same wallet", async () => {
+    const owner = await newUser();
+    await submitWish("My first wish", { owner });
+
+    // A second submission straight away is rejected
+    await expectError(submitWish("My rapid second wish", { owner }), "SubmitTooSoon");
+
+    // Once the cooldown has passed the next submission goes through
+    await sleep((30 + 2) * 1000);
+    await submitWish("My patient second wish", { owner });
+
+    const profile = await program.account.userProfile.fetch(
+      await findProfilePDA(owner.publicKey)
+    );
+    expect(profile.wishCount).to.equal(2);
+  });
+
+  it("Lets only the owner toggle a wish's visibility", async () => {
+    const wish = await submitWish("I wish to keep this one to myself", {
+      visibility: { private: {} },
+    });
+    let wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+    expect(wishAccount.visibility).to.deep.equal({ private: {} });
+
+    const setVisibility = (signer: anchor.web3.Keypair, visibility: object) =>
+      program.methods
+        .setVisibility(wish.wishId, visibility)
+        .accounts({ wish: wish.wishPDA, user: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    await expectError(
+      setVisibility(await newUser(), { public: {} }),
+      "UnauthorizedVisibilityChange"
+    );
+
+    await setVisibility(wish.owner, { public: {} });
+    wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+    expect(wishAccount.visibility).to.deep.equal({ public: {} });
+
+    await setVisibility(wish.owner, { private: {} });
+    wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+    expect(wishAccount.visibility).to.deep.equal({ private: {} });
+  });
+
+  it("Transfers ownership so only the new owner can delete the wish", async () => {
+    const wish = await submitWish("I wish someone else would take this on");
+    const newOwner = await newUser();
+
+    await program.methods
+      .transferWish(wish.wishId, newOwner.publicKey)
+      .accounts({
+        wish: wish.wishPDA,
+        stats: statsPDA,
+        fromProfile: await findProfilePDA(wish.owner.publicKey),
+        toProfile: await findProfilePDA(newOwner.publicKey),
+        fromIndex: await findWishIndexPDA(wish.owner.publicKey),
+        toIndex: await findWishIndexPDA(newOwner.publicKey),
+        currentOwner: wish.owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([wish.owner])
+      .rpc();
+
+    // The PDA address is unchanged; only the stored owner moves
+    const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+    expect(wishAccount.user.toString()).to.equal(newOwner.publicKey.toString());
+    expect(wishAccount.creator.toString()).to.equal(wish.owner.publicKey.toString());
+
+    // The previous owner can no longer delete it
+    await expectError(deleteWish(wish), "UnauthorizedDeletion");
+
+    await deleteWish({ ...wish, owner: newOwner });
+    expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+  });
+
+  describe("guardian", () => {
+    // Delete a wish, optionally with a co-signing guardian in remaining_accounts
+    const deleteWithGuardian = async (wish: SubmittedWish, guardian?: anchor.web3.Keypair) =>
+      program.methods
+        .deleteWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          escrow: await findEscrowPDA(wish.wishPDA),
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+          user: wish.owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          guardian ? [{ pubkey: guardian.publicKey, isSigner: true, isWritable: false }] : []
+        )
+        .signers(guardian ? [wish.owner, guardian] : [wish.owner])
+        .rpc();
+
+    it("Requires both the owner and the guardian to delete a guarded wish", async () => {
+      const guardian = anchor.web3.Keypair.generate();
+      const wish = await submitWish("I wish to keep my savings safe", {
+        guardian: guardian.publicKey,
+      });
+      expect((await program.account.aWish.fetch(wish.wishPDA)).guardian.toString()).to.equal(
+        guardian.publicKey.toString()
+      );
+
+      await expectError(deleteWithGuardian(wish), "GuardianSignatureMissing");
+      await expectError(
+        deleteWithGuardian(wish, anchor.web3.Keypair.generate()),
+        "GuardianSignatureMissing"
+      );
+
+      await deleteWithGuardian(wish, guardian);
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.be.null;
+    });
+
+    it("Lets the owner alone delete a wish without a guardian", async () => {
+      const wish = await submitWish("I wish for nobody to look over my shoulder");
+      expect((await program.account.aWish.fetch(wish.wishPDA)).guardian).to.be.null;
+
+      await deleteWithGuardian(wish);
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.be.null;
+    });
+  });
+
+  describe("tips", () => {
+    const tipWish = async (wish: SubmittedWish, tipper: anchor.web3.Keypair, amount: number) =>
+      program.methods
+        .tipWish(wish.wishId, new anchor.BN(amount))
+        .accounts({
+          wish: wish.wishPDA,
+          owner: wish.owner.publicKey,
+          stats: statsPDA,
+          treasury: treasuryPDA,
+          profile: await findProfilePDA(tipper.publicKey),
+          tipper: tipper.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([tipper])
+        .rpc();
+
+    it("Accumulates tips from multiple tippers and pays the owner", async () => {
+      const wish = await submitWish("I wish for a little support");
+      const ownerBefore = await provider.connection.getBalance(wish.owner.publicKey);
+
+      await tipWish(wish, await newUser(), 1_000_000);
+      await tipWish(wish, await newUser(), 2_500_000);
+
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.totalTipped.toNumber()).to.equal(3_500_000);
+
+      const ownerAfter = await provider.connection.getBalance(wish.owner.publicKey);
+      expect(ownerAfter - ownerBefore).to.equal(3_500_000);
+    });
+
+    it("Rejects a zero tip", async () => {
+      const wish = await submitWish("I wish for a real tip");
+      await expectError(tipWish(wish, await newUser(), 0), "ZeroTip");
+    });
+
+    it("Rejects a tip sent to someone other than the owner", async () => {
+      const wish = await submitWish("I wish my tips went to me");
+      const impostor = await newUser();
+      await expectError(
+        tipWish({ ...wish, owner: impostor }, await newUser(), 1_000),
+        "TipRecipientMismatch"
+      );
+    });
+
+    it("Pays tips and refunds to an owner that is a program-owned account", async () => {
+      // A board PDA stands in for an owner that is another program's PDA: it holds data and isn't
+      // owned by the system program
+      const wish = await submitWish("I wish to belong to a program");
+      const pdaOwner = await createBoard("tip-jar");
+      expect((await provider.connection.getAccountInfo(pdaOwner)).owner.toBase58()).to.equal(
+        program.programId.toBase58()
+      );
+
+      await program.methods
+        .transferWish(wish.wishId, pdaOwner)
+        .accounts({
+          wish: wish.wishPDA,
+          stats: statsPDA,
+          fromProfile: await findProfilePDA(wish.owner.publicKey),
+          toProfile: await findProfilePDA(pdaOwner),
+          fromIndex: await findWishIndexPDA(wish.owner.publicKey),
+          toIndex: await findWishIndexPDA(pdaOwner),
+          currentOwner: wish.owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([wish.owner])
+        .rpc();
+
+      const tipper = await newUser();
+      const beforeTip = await provider.connection.getBalance(pdaOwner);
+      await program.methods
+        .tipWish(wish.wishId, new anchor.BN(1_000_000))
+        .accounts({
+          wish: wish.wishPDA,
+          owner: pdaOwner,
+          stats: statsPDA,
+          treasury: treasuryPDA,
+          profile: await findProfilePDA(tipper.publicKey),
+          tipper: tipper.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([tipper])
+        .rpc();
+      expect((await provider.connection.getBalance(pdaOwner)) - beforeTip).to.equal(1_000_000);
+      expect((await program.account.aWish.fetch(wish.wishPDA)).totalTipped.toNumber()).to.equal(1_000_000);
+
+      // Closing the wish refunds its rent to the PDA owner as well
+      const rent = await provider.connection.getBalance(wish.wishPDA);
+      const beforeRemoval = await provider.connection.getBalance(pdaOwner);
+      await program.methods
+        .adminRemoveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(pdaOwner),
+          wishIndex: await findWishIndexPDA(pdaOwner),
+          owner: pdaOwner,
+          admin: user.publicKey,
+        })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      expect((await provider.connection.getBalance(pdaOwner)) - beforeRemoval).to.equal(rent);
+    });
+
+    describe("matching", () => {
+      const setMatching = (ratioBps: number, pool: number, admin?: anchor.web3.Keypair) =>
+        program.methods
+          .setMatching(ratioBps, new anchor.BN(pool))
+          .accounts({ stats: statsPDA, admin: admin ? admin.publicKey : user.publicKey })
+          .signers(admin ? [admin] : [])
+          .rpc();
+
+      it("Matches tips from the treasury until the pool runs out", async () => {
+        const wish = await submitWish("I wish for a matched tip");
+        const tipper = await newUser();
+        await expectError(setMatching(5_000, 600_000, await newUser()), "Unauthorized");
+
+        // Make sure the treasury holds more than the pool, on top of its rent reserve
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            anchor.web3.SystemProgram.transfer({
+              fromPubkey: user.publicKey,
+              toPubkey: treasuryPDA,
+              lamports: 1_000_000,
+            })
+          )
+        );
+
+        await setMatching(5_000, 600_000); // 50%
+        try {
+          const balance = () => provider.connection.getBalance(wish.owner.publicKey);
+          let before = await balance();
+          await tipWish(wish, tipper, 1_000_000);
+          expect((await balance()) - before).to.equal(1_000_000 + 500_000);
+
+          // Only 100_000 left in the pool, so the match is capped
+          before = await balance();
+          await tipWish(wish, tipper, 1_000_000);
+          expect((await balance()) - before).to.equal(1_000_000 + 100_000);
+          expect((await program.account.wallStats.fetch(statsPDA)).matchPoolRemaining.toNumber()).to.equal(0);
+
+          // Pool exhausted: the tip still goes through, unmatched
+          before = await balance();
+          const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+          await tipWish(wish, tipper, 1_000_000);
+          expect((await balance()) - before).to.equal(1_000_000);
+          expect(await provider.connection.getBalance(treasuryPDA)).to.equal(treasuryBefore);
+
+          // Matches aren't tips, so they don't count towards the wish's total
+          const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+          expect(wishAccount.totalTipped.toNumber()).to.equal(3_000_000);
+        } finally {
+          await setMatching(0, 0);
+        }
+      });
+
+      it("Refuses a self-tip without touching the pool", async () => {
+        const wish = await submitWish("I wish to tip myself");
+        await setMatching(5_000, 600_000);
+        try {
+          const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+          await expectError(tipWish(wish, wish.owner, 1_000_000), "SelfTip");
+
+          expect((await program.account.wallStats.fetch(statsPDA)).matchPoolRemaining.toNumber()).to.equal(600_000);
+          expect(await provider.connection.getBalance(treasuryPDA)).to.equal(treasuryBefore);
+          expect((await program.account.aWish.fetch(wish.wishPDA)).totalTipped.toNumber()).to.equal(0);
+        } finally {
+          await setMatching(0, 0);
+        }
+      });
+    });
+  });
+
+  describe("escrow", () => {
+    const findSponsorshipPDA = async (
+      wishPDA: anchor.web3.PublicKey,
+      sponsor: anchor.web3.PublicKey
+    ) => {
+      const [sponsorshipPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("sponsorship"), wishPDA.toBuffer(), sponsor.toBuffer()],
+        program.programId
+      );
+      return sponsorshipPDA;
+    };
+
+    const sponsorWish = async (wish: SubmittedWish, sponsor: anchor.web3.Keypair, amount: number) =>
+      program.methods
+        .sponsorWish(wish.wishId, new anchor.BN(amount))
+        .accounts({
+          wish: wish.wishPDA,
+          escrow: await findEscrowPDA(wish.wishPDA),
+          sponsorship: await findSponsorshipPDA(wish.wishPDA, sponsor.publicKey),
+          sponsor: sponsor.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([sponsor])
+        .rpc();
+
+    const releaseEscrow = async (wish: SubmittedWish, signer: anchor.web3.Keypair) =>
+      program.methods
+        .releaseEscrow(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          escrow: await findEscrowPDA(wish.wishPDA),
+          user: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const refundEscrow = async (wish: SubmittedWish, sponsor: anchor.web3.Keypair) =>
+      program.methods
+        .refundEscrow()
+        .accounts({
+          wish: wish.wishPDA,
+          escrow: await findEscrowPDA(wish.wishPDA),
+          sponsorship: await findSponsorshipPDA(wish.wishPDA, sponsor.publicKey),
+          sponsor: sponsor.publicKey,
+        })
+        .signers([sponsor])
+        .rpc();
+
+    it("Holds sponsorships in escrow until the fulfilled wish is released", async () => {
+      const wish = await submitWish("I wish to build a community garden");
+      const alice = await newUser();
+      const bob = await newUser();
+      const escrowPDA = await findEscrowPDA(wish.wishPDA);
+
+      await sponsorWish(wish, alice, 1_000_000);
+      await sponsorWish(wish, bob, 2_000_000);
+      await sponsorWish(wish, alice, 500_000);
+
+      const escrow = await program.account.escrow.fetch(escrowPDA);
+      expect(escrow.total.toNumber()).to.equal(3_500_000);
+      expect(escrow.sponsors).to.equal(2);
+      const sponsorship = await program.account.sponsorship.fetch(
+        await findSponsorshipPDA(wish.wishPDA, alice.publicKey)
+      );
+      expect(sponsorship.amount.toNumber()).to.equal(1_500_000);
+
+      // Locked until the wish is fulfilled, and only the owner can release it
+      await expectError(releaseEscrow(wish, wish.owner), "WishNotFulfilled");
+      await program.methods
+        .markFulfilled(wish.wishId)
+        .accounts({ wish: wish.wishPDA, user: wish.owner.publicKey })
+        .signers([wish.owner])
+        .rpc();
+      await expectError(releaseEscrow(wish, alice), "UnauthorizedRelease");
+      await expectError(sponsorWish(wish, bob, 1_000_000), "AlreadyFulfilled");
+
+      const escrowBalance = await provider.connection.getBalance(escrowPDA);
+      const ownerBefore = await provider.connection.getBalance(wish.owner.publicKey);
+      await releaseEscrow(wish, wish.owner);
+      const ownerAfter = await provider.connection.getBalance(wish.owner.publicKey);
+
+      expect(ownerAfter - ownerBefore).to.equal(escrowBalance);
+      expect(await provider.connection.getAccountInfo(escrowPDA)).to.be.null;
+    });
+
+    it("Refunds sponsors once the wish is removed unfulfilled", async () => {
+      const wish = await submitWish("I wish to sail around the world");
+      const sponsor = await newUser();
+      const escrowPDA = await findEscrowPDA(wish.wishPDA);
+
+      await sponsorWish(wish, sponsor, 1_000_000);
+      await expectError(refundEscrow(wish, sponsor), "WishStillExists");
+
+      await program.methods
+        .adminRemoveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+          owner: wish.owner.publicKey,
+          admin: user.publicKey,
+        })
+        .rpc();
+      const before = await provider.connection.getBalance(sponsor.publicKey);
+      await refundEscrow(wish, sponsor);
+      const after = await provider.connection.getBalance(sponsor.publicKey);
+
+      // The contribution plus the rent of the sponsorship and (as the last sponsor) the escrow
+      expect(after - before).to.be.greaterThan(1_000_000);
+      expect(await provider.connection.getAccountInfo(escrowPDA)).to.be.null;
+    });
+
+    it("Lets the owner refund an unfulfilled wish's sponsors so it can be deleted", async () => {
+      const wish = await submitWish("I wish I hadn't asked for this");
+      const [alice, bob] = [await newUser(), await newUser()];
+      const escrowPDA = await findEscrowPDA(wish.wishPDA);
+      await sponsorWish(wish, alice, 1_000_000);
+      await sponsorWish(wish, bob, 2_000_000);
+      await expectError(deleteWish(wish), "OutstandingFunds");
+
+      const refundSponsor = (sponsor: anchor.web3.PublicKey, signer: anchor.web3.Keypair) =>
+        findSponsorshipPDA(wish.wishPDA, sponsor).then((sponsorship) =>
+          program.methods
+            .refundSponsor(wish.wishId)
+            .accounts({ wish: wish.wishPDA, escrow: escrowPDA, sponsorship, sponsor, owner: signer.publicKey })
+            .signers([signer])
+            .rpc()
+        );
+
+      // Sponsors can't pull out of a wish that is still up; only the owner can refund them
+      await expectError(refundEscrow(wish, alice), "WishStillExists");
+      await expectError(refundSponsor(bob.publicKey, alice), "UnauthorizedRefund");
+      await refundSponsor(alice.publicKey, wish.owner);
+      expect((await program.account.escrow.fetch(escrowPDA)).total.toNumber()).to.equal(2_000_000);
+
+      // The last refund closes the escrow
+      const bobBefore = await provider.connection.getBalance(bob.publicKey);
+      await refundSponsor(bob.publicKey, wish.owner);
+      const bobAfter = await provider.connection.getBalance(bob.publicKey);
+      expect(bobAfter - bobBefore).to.be.greaterThan(2_000_000); // Plus the sponsorship and escrow rent
+      expect(await provider.connection.getAccountInfo(escrowPDA)).to.be.null;
+
+      await deleteWish(wish);
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.be.null;
+    });
+
+    it("Stops refunds once the wish is fulfilled", async () => {
+      const wish = await submitWish("I wish to keep my promise");
+      const sponsor = await newUser();
+      await sponsorWish(wish, sponsor, 1_000_000);
+      await program.methods
+        .markFulfilled(wish.wishId)
+        .accounts({ wish: wish.wishPDA, user: wish.owner.publicKey })
+        .signers([wish.owner])
+        .rpc();
+
+      await expectError(refundEscrow(wish, sponsor), "WishStillExists");
+      await expectError(
+        program.methods
+          .refundSponsor(wish.wishId)
+          .accounts({
+            wish: wish.wishPDA,
+            escrow: await findEscrowPDA(wish.wishPDA),
+            sponsorship: await findSponsorshipPDA(wish.wishPDA, sponsor.publicKey),
+            sponsor: sponsor.publicKey,
+            owner: wish.owner.publicKey,
+          })
+          .signers([wish.owner])
+          .rpc(),
+        "AlreadyFulfilled"
+      );
+    });
+
+    it("Won't let the owner delete a wish while its escrow holds sponsorships", async () => {
+      const wish = await submitWish("I wish for a well-funded wish");
+      await sponsorWish(wish, await newUser(), 1_000_000);
+
+      await expectError(deleteWish(wish), "OutstandingFunds");
+      await expectError(
+        program.methods
+          .deleteAll()
+          .accounts({
+            board: wish.board,
+            stats: statsPDA,
+            profile: await findProfilePDA(wish.owner.publicKey),
+            wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+            user: wish.owner.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: wish.wishPDA, isSigner: false, isWritable: true },
+            { pubkey: await findEscrowPDA(wish.wishPDA), isSigner: false, isWritable: false },
+          ])
+          .signers([wish.owner])
+          .rpc(),
+        "OutstandingFunds"
+      );
+
+      // Once fulfilled and released, the escrow is gone and the wish can go too
+      await program.methods
+        .markFulfilled(wish.wishId)
+        .accounts({ wish: wish.wishPDA, user: wish.owner.publicKey })
+        .signers([wish.owner])
+        .rpc();
+      await releaseEscrow(wish, wish.owner);
+      await deleteWish(wish);
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+    });
+
+    it("Rejects a zero sponsorship", async () => {
+      const wish = await submitWish("I wish for real support");
+      await expectError(sponsorWish(wish, await newUser(), 0), "ZeroSponsorship");
+    });
+  });
+
+  describe("pause switch", () => {
+    const setPaused = (paused: boolean, admin?: anchor.web3.Keypair) =>
+      program.methods
+        .setPaused(paused)
+        .accounts({ stats: statsPDA, admin: admin ? admin.publicKey : user.publicKey })
+        .signers(admin ? [admin] : [])
+        .rpc();
+
+    it("Only the admin can pause the wall", async () => {
+      await expectError(setPaused(true, await newUser()), "Unauthorized");
+
+      const stats = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.admin.toString()).to.equal(user.publicKey.toString());
+      expect(stats.paused).to.be.false;
+    });
+
+    it("Rejects submissions while paused but still allows deletes", async () => {
+      const wish = await submitWish("I wish to be deleted during an incident");
+
+      await setPaused(true);
+      try {
+        await expectError(submitWish("I wish to sneak in while paused"), "WallPaused");
+
+        await deleteWish(wish);
+        expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      } finally {
+        await setPaused(false);
+      }
+
+      await submitWish("I wish to post after the incident");
+    });
+  });
+
+  describe("fees", () => {
+    const setFee = (fee: number) =>
+      program.methods
+        .setFee(new anchor.BN(fee))
+        .accounts({ stats: statsPDA, admin: user.publicKey })
+        .rpc();
+
+    const withdraw = (amount: number, admin?: anchor.web3.Keypair) =>
+      program.methods
+        .withdrawTreasury(new anchor.BN(amount))
+        .accounts({
+          stats: statsPDA,
+          treasury: treasuryPDA,
+          admin: admin ? admin.publicKey : user.publicKey,
+        })
+        .signers(admin ? [admin] : [])
+        .rpc();
+
+    it("Charges the fee into the treasury and lets only the admin withdraw it", async () => {
+      const fee = 5_000_000;
+      const owner = await newUser();
+      const { connection } = provider;
+      const title = "I wish to support the wall";
+
+      await setFee(fee);
+      let treasuryBefore: number, ownerAfter: number, treasuryAfter: number;
+      const ownerBefore = await connection.getBalance(owner.publicKey);
+      try {
+        treasuryBefore = await connection.getBalance(treasuryPDA);
+        await submitWish(title, { owner });
+        ownerAfter = await connection.getBalance(owner.publicKey);
+        treasuryAfter = await connection.getBalance(treasuryPDA);
+      } finally {
+        await setFee(0);
+      }
+
+      // The owner pays the fee plus rent for the wish and their new profile and wish index; the
+      // provider pays tx fees
+      const rent =
+        (await connection.getMinimumBalanceForRentExemption(wishSpace(title))) +
+        (await connection.getMinimumBalanceForRentExemption(profileSpace)) +
+        (await connection.getMinimumBalanceForRentExemption(wishIndexSpace(4)));
+      expect(ownerBefore - ownerAfter).to.equal(fee + rent);
+      expect(treasuryAfter - treasuryBefore).to.equal(fee);
+
+      await expectError(withdraw(fee, await newUser()), "Unauthorized");
+
+      await withdraw(fee);
+      expect(await connection.getBalance(treasuryPDA)).to.equal(treasuryBefore);
+      await expectError(withdraw(treasuryAfter), "InsufficientTreasury");
+    });
+
+    it("Charges nothing with the fee at zero", async () => {
+      const before = await provider.connection.getBalance(treasuryPDA);
+      await submitWish("I wish for a free wall");
+      expect(await provider.connection.getBalance(treasuryPDA)).to.equal(before);
+    });
+  });
+
+  describe("wish cap", () => {
+    const setWishCap = (max: number, admin?: anchor.web3.Keypair) =>
+      program.methods
+        .setWishCap(max)
+        .accounts({ stats: statsPDA, admin: admin ? admin.publicKey : user.publicKey })
+        .signers(admin ? [admin] : [])
+        .rpc();
+    const activeCount = async (owner: anchor.web3.PublicKey) =>
+      (await program.account.userProfile.fetch(await findProfilePDA(owner))).activeCount;
+
+    it("Only lets the admin set the cap", async () => {
+      await expectError(setWishCap(2, await newUser()), "Unauthorized");
+    });
+
+    it("Keeps owners' counts in sync through archiving, transfers and admin removal", async () => {
+      const wish = await submitWish("I wish to be counted correctly");
+      const newOwner = await newUser();
+      expect(await activeCount(wish.owner.publicKey)).to.equal(1);
+
+      await program.methods
+        .archiveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          user: wish.owner.publicKey,
+        })
+        .signers([wish.owner])
+        .rpc();
+      expect(await activeCount(wish.owner.publicKey)).to.equal(0);
+      await program.methods
+        .unarchiveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          user: wish.owner.publicKey,
+        })
+        .signers([wish.owner])
+        .rpc();
+      expect(await activeCount(wish.owner.publicKey)).to.equal(1);
+
+      await program.methods
+        .transferWish(wish.wishId, newOwner.publicKey)
+        .accounts({
+          wish: wish.wishPDA,
+          stats: statsPDA,
+          fromProfile: await findProfilePDA(wish.owner.publicKey),
+          toProfile: await findProfilePDA(newOwner.publicKey),
+          fromIndex: await findWishIndexPDA(wish.owner.publicKey),
+          toIndex: await findWishIndexPDA(newOwner.publicKey),
+          currentOwner: wish.owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([wish.owner])
+        .rpc();
+      expect(await activeCount(wish.owner.publicKey)).to.equal(0);
+      expect(await activeCount(newOwner.publicKey)).to.equal(1);
+
+      await program.methods
+        .adminRemoveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(newOwner.publicKey),
+          wishIndex: await findWishIndexPDA(newOwner.publicKey),
+          owner: newOwner.publicKey,
+          admin: user.publicKey,
+        })
+        .rpc();
+      expect(await activeCount(newOwner.publicKey)).to.equal(0);
+    });
+  });
+
+  describe("pins", () => {
+    const pinWish = (wish: SubmittedWish, durationSecs: number, signer = wish.owner) =>
+      program.methods
+        .pinWish(wish.wishId, new anchor.BN(durationSecs))
+        .accounts({
+          wish: wish.wishPDA,
+          treasury: treasuryPDA,
+          user: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("Charges per second into the treasury and extends an existing pin", async () => {
+      const wish = await submitWish("I wish to stay on top");
+      const rate = 1_000; // PIN_LAMPORTS_PER_SEC
+
+      const ownerBefore = await provider.connection.getBalance(wish.owner.publicKey);
+      const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+      await pinWish(wish, 600);
+      expect(ownerBefore - (await provider.connection.getBalance(wish.owner.publicKey))).to.equal(600 * rate);
+      expect((await provider.connection.getBalance(treasuryPDA)) - treasuryBefore).to.equal(600 * rate);
+
+      const firstPin = (await program.account.aWish.fetch(wish.wishPDA)).pinnedUntil.toNumber();
+      expect(firstPin).to.be.greaterThan(Date.now() / 1000);
+
+      // Still pinned, so the new time is added on top of the current pin
+      await pinWish(wish, 300);
+      const secondPin = (await program.account.aWish.fetch(wish.wishPDA)).pinnedUntil.toNumber();
+      expect(secondPin).to.equal(firstPin + 300);
+    });
+
+    it("Only lets the owner pin their wish", async () => {
+      const wish = await submitWish("I wish nobody else could pin me");
+      await expectError(pinWish(wish, 60, await newUser()), "UnauthorizedPin");
+      await expectError(pinWish(wish, 0), "ZeroPinDuration");
+    });
+  });
+
+  describe("claims", () => {
+    const claimWish = (wish: SubmittedWish, claimer: anchor.web3.Keypair) =>
+      program.methods
+        .claimWish(wish.wishId)
+        .accounts({ wish: wish.wishPDA, claimer: claimer.publicKey })
+        .signers([claimer])
+        .rpc();
+    const unclaimWish = (wish: SubmittedWish, signer: anchor.web3.Keypair) =>
+      program.methods
+        .unclaimWish(wish.wishId)
+        .accounts({ wish: wish.wishPDA, signer: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    it("Allows one claimer at a time and lets them or the owner unclaim", async () => {
+      const wish = await submitWish("I wish someone would teach me to sail");
+      const volunteer = await newUser();
+      const other = await newUser();
+
+      await expectError(unclaimWish(wish, volunteer), "NotClaimed");
+      await claimWish(wish, volunteer);
+      let account = await program.account.aWish.fetch(wish.wishPDA);
+      expect(account.claimedBy.toBase58()).to.equal(volunteer.publicKey.toBase58());
+      expect(account.claimedAt.toNumber()).to.be.greaterThan(0);
+
+      await expectError(claimWish(wish, other), "AlreadyClaimed");
+      await expectError(unclaimWish(wish, other), "UnauthorizedUnclaim");
+
+      await unclaimWish(wish, volunteer);
+      account = await program.account.aWish.fetch(wish.wishPDA);
+      expect(account.claimedBy).to.be.null;
+      expect(account.claimedAt.toNumber()).to.equal(0);
+
+      // The owner can also release a claim
+      await claimWish(wish, other);
+      await unclaimWish(wish, wish.owner);
+      expect((await program.account.aWish.fetch(wish.wishPDA)).claimedBy).to.be.null;
+    });
+
+    it("Records the claimer as the granter when the wish is fulfilled", async () => {
+      const wish = await submitWish("I wish for a hand-knitted scarf");
+      const volunteer = await newUser();
+      await claimWish(wish, volunteer);
+
+      await program.methods
+        .markFulfilled(wish.wishId)
+        .accounts({ wish: wish.wishPDA, user: wish.owner.publicKey })
+        .signers([wish.owner])
+        .rpc();
+      const account = await program.account.aWish.fetch(wish.wishPDA);
+      expect(account.grantedBy.toBase58()).to.equal(volunteer.publicKey.toBase58());
+
+      await expectError(claimWish(wish, await newUser()), "AlreadyFulfilled");
+    });
+  });
+
+  describe("verify", () => {
+    const verifyWish = (wishId: anchor.BN, wish: anchor.web3.PublicKey, expectedOwner: anchor.web3.PublicKey) =>
+      program.methods.verifyWish(wishId, expectedOwner).accounts({ wish }).rpc();
+
+    it("Succeeds for the owner and fails for anyone else", async () => {
+      const wish = await submitWish("I wish to be verified");
+      await verifyWish(wish.wishId, wish.wishPDA, wish.owner.publicKey);
+      await expectError(
+        verifyWish(wish.wishId, wish.wishPDA, anchor.web3.Keypair.generate().publicKey),
+        "WishOwnerMismatch"
+      );
+    });
+
+    it("Rejects accounts that aren't the wish PDA", async () => {
+      const wish = await submitWish("I wish to stay genuine");
+      // Right account, wrong id
+      await expectError(
+        verifyWish(wish.wishId.addn(1), wish.wishPDA, wish.owner.publicKey),
+        "ConstraintSeeds"
+      );
+      // A program account that isn't a wish
+      await expectError(
+        verifyWish(wish.wishId, await findProfilePDA(wish.owner.publicKey), wish.owner.publicKey),
+        "AccountDiscriminatorMismatch"
+      );
+    });
+
+    it("Matches the documented byte offsets", async () => {
+      const wish = await submitWish("I wish my layout was documented", { category: { travel: {} } });
+      const data = (await provider.connection.getAccountInfo(wish.wishPDA)).data;
+      const account = await program.account.aWish.fetch(wish.wishPDA);
+
+      expect(data[8]).to.equal(2); // WISH_VERSION
+      expect(new anchor.web3.PublicKey(data.subarray(9, 41)).toBase58()).to.equal(wish.owner.publicKey.toBase58());
+      expect(new anchor.BN(data.subarray(41, 49), "le").eq(wish.wishId)).to.be.true;
+      expect(data[66]).to.equal(0); // Travel
+      expect(new anchor.web3.PublicKey(data.subarray(67, 99)).toBase58()).to.equal(wish.owner.publicKey.toBase58());
+      expect(new anchor.BN(data.subarray(138, 146), "le").eq(account.index)).to.be.true;
+      expect(data[210]).to.equal(0); // Not archived
+      expect(data[211]).to.equal(0); // Not sealed
+      expect(new anchor.web3.PublicKey(data.subarray(212, 244)).toBase58()).to.equal(mainBoardPDA.toBase58());
+      expect(new anchor.BN(data.subarray(244, 252), "le").toNumber()).to.equal(0); // No weighted likes
+      expect(new anchor.BN(data.subarray(252, 260), "le").toNumber()).to.equal(0); // No delete requested
+      expect(data[260]).to.equal(0); // No guardian
+    });
+
+    it("Finds a wallet's wishes with a memcmp on user at WISH_USER_OFFSET", async () => {
+      // The offsets are exported in the IDL, so indexers don't have to hardcode them
+      const constant = (name: string) => Number(program.idl.constants.find((c) => c.name === name).value);
+      expect(constant("WISH_USER_OFFSET")).to.equal(9);
+      expect(constant("WISH_CATEGORY_OFFSET")).to.equal(66);
+      expect(constant("WISH_CREATOR_OFFSET")).to.equal(67);
+      expect(constant("WISH_INDEX_OFFSET")).to.equal(138);
+      expect(constant("WISH_BOARD_OFFSET")).to.equal(212);
+
+      // Titles and bodies of different lengths don't move the field
+      // One batch, so the cooldown doesn't get in the way
+      const owner = await newUser();
+      const [short, long] = await submitMany(owner, 2, [
+        { title: "I wish" },
+        {
+          title: "I wish for a much longer title than the other one",
+          body: "and a body to go with it",
+          guardian: anchor.web3.Keypair.generate().publicKey,
+        },
+      ]);
+      const other = await submitWish("I wish to be left out of the filter");
+
+      const found = await program.account.aWish.all([
+        { memcmp: { offset: constant("WISH_USER_OFFSET"), bytes: owner.publicKey.toBase58() } },
+      ]);
+      const keys = found.map((w) => w.publicKey.toBase58()).sort();
+      expect(keys).to.deep.equal([short.wishPDA.toBase58(), long.wishPDA.toBase58()].sort());
+      expect(keys).to.not.include(other.wishPDA.toBase58());
+    });
+  });
+
+  describe("two-step delete", () => {
+    const scheduleDelete = (method: "requestDelete" | "cancelDelete", wish: SubmittedWish, signer = wish.owner) =>
+      program.methods[method](wish.wishId)
+        .accounts({ wish: wish.wishPDA, user: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    const confirmDelete = async (wish: SubmittedWish) =>
+      program.methods
+        .confirmDelete(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          escrow: await findEscrowPDA(wish.wishPDA),
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+          user: wish.owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([wish.owner])
+        .rpc();
+
+    const setTwoStepDelete = (enabled: boolean) =>
+      program.methods.setTwoStepDelete(enabled).accounts({ stats: statsPDA, admin: user.publicKey }).rpc();
+
+    it("Records a delete request until it is cancelled", async () => {
+      const wish = await submitWish("I wish to think it over");
+
+      await scheduleDelete("requestDelete", wish);
+      let wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.deleteRequestedAt.toNumber()).to.be.greaterThan(0);
+
+      // The grace period hasn't passed, so the wish can't be closed yet
+      await expectError(confirmDelete(wish), "DeleteNotReady");
+
+      await scheduleDelete("cancelDelete", wish);
+      wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.deleteRequestedAt.toNumber()).to.equal(0);
+    });
+
+    it("Only lets the owner request a delete", async () => {
+      const wish = await submitWish("I wish to stay put");
+      await expectError(scheduleDelete("requestDelete", wish, await newUser()), "UnauthorizedDeletion");
+    });
+
+    it("Refuses immediate deletes while the wall requires two steps", async () => {
+      const wish = await submitWish("I wish to be deleted carefully");
+
+      await setTwoStepDelete(true);
+      try {
+        await expectError(deleteWish(wish), "DeleteNotReady");
+      } finally {
+        await setTwoStepDelete(false);
+      }
+
+      await deleteWish(wish);
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.be.null;
+    });
+  });
+
+  describe("anonymous wishes", () => {
+    // Delete an anonymous wish, whose index belongs to its anonymous key
+    const deleteAnonymous = async (wish: SubmittedWish, signer: anchor.web3.Keypair) => {
+      const key = anonymousOwner(wish.board, wish.owner.publicKey, wish.wishId);
+      return program.methods
+        .deleteWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          escrow: await findEscrowPDA(wish.wishPDA),
+          stats: statsPDA,
+          profile: await findProfilePDA(signer.publicKey),
+          wishIndex: await findWishIndexPDA(key),
+          user: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    it("Keeps the submitter's pubkey out of the wish's accounts", async () => {
+      const wish = await submitWish("I wish nobody knew this", { anonymous: true });
+      const key = anonymousOwner(wish.board, wish.owner.publicKey, wish.wishId);
+
+      const account = await provider.connection.getAccountInfo(wish.wishPDA);
+      expect(account.data.includes(wish.owner.publicKey.toBuffer())).to.be.false;
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.user.equals(anchor.web3.PublicKey.default)).to.be.true;
+      expect(wishAccount.creator.equals(key)).to.be.true;
+
+      // The wish is listed under the anonymous key, and the wallet got no index of its own
+      const index = await program.account.wishIndex.fetch(await findWishIndexPDA(key));
+      expect(index.wishes.map((pubkey) => pubkey.toBase58())).to.deep.equal([wish.wishPDA.toBase58()]);
+      expect(await program.account.wishIndex.fetchNullable(await findWishIndexPDA(wish.owner.publicKey))).to.be.null;
+    });
+
+    it("Can only be deleted by the wallet that submitted it, which gets all of the rent back", async () => {
+      const wish = await submitWish("I wish to take this back", { anonymous: true });
+      const indexPDA = await findWishIndexPDA(anonymousOwner(wish.board, wish.owner.publicKey, wish.wishId));
+      const profilePDA = await findProfilePDA(wish.owner.publicKey);
+      expect((await program.account.userProfile.fetch(profilePDA)).activeCount).to.equal(1);
+
+      await expectError(deleteAnonymous(wish, await newUser()), "UnauthorizedDeletion");
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.not.be.null;
+
+      // The wish and its one-wish index both close; the profile is the owner's own and stays
+      const rent = (await provider.connection.getBalance(wish.wishPDA)) + (await provider.connection.getBalance(indexPDA));
+      const before = await provider.connection.getBalance(wish.owner.publicKey);
+      await deleteAnonymous(wish, wish.owner);
+      const after = await provider.connection.getBalance(wish.owner.publicKey);
+
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.be.null;
+      expect(await provider.connection.getAccountInfo(indexPDA)).to.be.null;
+      expect(after - before).to.equal(rent);
+      expect((await program.account.userProfile.fetch(profilePDA)).activeCount).to.equal(0);
+    });
+
+    it("Still applies the submitter's cooldown to an anonymous wish", async () => {
+      const wish = await submitWish("I wish to be seen first");
+      await expectError(
+        submitWish("I wish to slip in unseen", { owner: wish.owner, anonymous: true }),
+        "SubmitTooSoon"
+      );
+    });
+
+    it("Can't be given an expiry, since nobody could reap it", async () => {
+      await expectError(submitWish("I wish to fade away unseen", { anonymous: true, ttlSecs: 60 }), "AnonymousExpiry");
+    });
+
+    it("Holds the rent of an admin-removed anonymous wish until its submitter claims it", async () => {
+      const wish = await submitWish("I wish to say something rude anonymously", { anonymous: true });
+      const indexPDA = await findWishIndexPDA(anonymousOwner(wish.board, wish.owner.publicKey, wish.wishId));
+      const profilePDA = await findProfilePDA(wish.owner.publicKey);
+      const claim = (signer: anchor.web3.Keypair) =>
+        program.methods
+          .claimAnonymousRent(wish.board, wish.wishId)
+          .accounts({ wishIndex: indexPDA, profile: profilePDA, user: signer.publicKey })
+          .signers([signer])
+          .rpc();
+      const removeAnonymous = (admin?: anchor.web3.Keypair) =>
+        program.methods
+          .adminRemoveAnonymousWish(wish.wishId)
+          .accounts({
+            wish: wish.wishPDA,
+            board: wish.board,
+            stats: statsPDA,
+            wishIndex: indexPDA,
+            admin: admin ? admin.publicKey : user.publicKey,
+          })
+          .signers(admin ? [admin] : [])
+          .rpc();
+
+      // The regular removal has no owner to send the rent to
+      await expectError(
+        program.methods
+          .adminRemoveWish(wish.wishId)
+          .accounts({
+            wish: wish.wishPDA,
+            board: wish.board,
+            stats: statsPDA,
+            profile: await findProfilePDA(anchor.web3.PublicKey.default),
+            wishIndex: await findWishIndexPDA(anchor.web3.PublicKey.default),
+            owner: user.publicKey,
+            admin: user.publicKey,
+          })
+          .rpc(),
+        "AnonymousWish"
+      );
+      await expectError(claim(wish.owner), "WishNotRemoved");
+      await expectError(removeAnonymous(await newUser()), "Unauthorized");
+
+      const rent = await provider.connection.getBalance(wish.wishPDA);
+      const indexRent = await provider.connection.getBalance(indexPDA);
+      await removeAnonymous();
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      expect(await provider.connection.getBalance(indexPDA)).to.equal(rent + indexRent);
+
+      // Someone else's signature derives a different anonymous key
+      await expectError(claim(await newUser()), "ConstraintSeeds");
+
+      const before = await provider.connection.getBalance(wish.owner.publicKey);
+      await claim(wish.owner);
+      const after = await provider.connection.getBalance(wish.owner.publicKey);
+      expect(after - before).to.equal(rent + indexRent);
+      expect(await provider.connection.getAccountInfo(indexPDA)).to.be.null;
+      expect((await program.account.userProfile.fetch(profilePDA)).activeCount).to.equal(0);
+    });
+  });
+
+  describe("seal", () => {
+    const sealWish = (wish: SubmittedWish, signer = wish.owner) =>
+      program.methods
+        .sealWish(wish.wishId)
+        .accounts({ wish: wish.wishPDA, user: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    it("Stops the owner from editing, archiving or deleting a sealed wish", async () => {
+      const wish = await submitWish("I wish to run a marathon this year");
+      await expectError(sealWish(wish, await newUser()), "UnauthorizedSeal");
+      await sealWish(wish);
+      expect((await program.account.aWish.fetch(wish.wishPDA)).sealed).to.be.true;
+      await expectError(sealWish(wish), "AlreadySealed");
+
+      await expectError(deleteWish(wish), "WishSealed");
+      await expectError(
+        program.methods
+          .updateWish(wish.wishId, "I wish to run a 5k this year", "")
+          .accounts({
+            wish: wish.wishPDA,
+            user: wish.owner.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([wish.owner])
+          .rpc(),
+        "WishSealed"
+      );
+      await expectError(
+        program.methods
+          .archiveWish(wish.wishId)
+          .accounts({
+            wish: wish.wishPDA,
+            board: wish.board,
+            stats: statsPDA,
+            profile: await findProfilePDA(wish.owner.publicKey),
+            user: wish.owner.publicKey,
+          })
+          .signers([wish.owner])
+          .rpc(),
+        "WishSealed"
+      );
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.not.be.null;
+    });
+
+    it("Still lets the admin remove a sealed wish", async () => {
+      const wish = await submitWish("I wish to say something I shouldn't");
+      await sealWish(wish);
+
+      await program.methods
+        .adminRemoveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+          owner: wish.owner.publicKey,
+          admin: user.publicKey,
+        })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+    });
+  });
+
+  describe("archive", () => {
+    const setArchived = async (wish: SubmittedWish, archived: boolean, signer = wish.owner) => {
+      return program.methods[archived ? "archiveWish" : "unarchiveWish"](wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(signer.publicKey),
+          user: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    };
+    const activeCount = async () => (await program.account.wallStats.fetch(statsPDA)).active.toNumber();
+
+    it("Takes an archived wish off the active count and restores it when unarchived", async () => {
+      const wish = await submitWish("I wish to take a break");
+      const active = await activeCount();
+
+      await expectError(setArchived(wish, true, await newUser()), "UnauthorizedArchive");
+      await expectError(setArchived(wish, false), "NotArchived");
+
+      await setArchived(wish, true);
+      expect((await program.account.aWish.fetch(wish.wishPDA)).archived).to.be.true;
+      expect(await activeCount()).to.equal(active - 1);
+      await expectError(setArchived(wish, true), "AlreadyArchived");
+
+      await setArchived(wish, false);
+      expect((await program.account.aWish.fetch(wish.wishPDA)).archived).to.be.false;
+      expect(await activeCount()).to.equal(active);
+    });
+
+    it("Keeps likes and still lets the owner delete an archived wish", async () => {
+      const wish = await submitWish("I wish to be remembered");
+      const voter = await newUser();
+      await program.methods
+        .likeWish()
+        .accounts({
+          like: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("like"), wish.wishPDA.toBuffer(), voter.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          wish: wish.wishPDA,
+          stats: statsPDA,
+          profile: await findProfilePDA(voter.publicKey),
+          voter: voter.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+      await setArchived(wish, true);
+      expect((await program.account.aWish.fetch(wish.wishPDA)).likes.toNumber()).to.equal(1);
+
+      // Already off the active count, so deleting doesn't take it off a second time
+      const active = await activeCount();
+      await deleteWish(wish);
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      expect(await activeCount()).to.equal(active);
+    });
+  });
+
+  describe("admin handoff", () => {
+    const proposeAdmin = (newAdmin: anchor.web3.PublicKey, admin?: anchor.web3.Keypair) =>
+      program.methods
+        .proposeAdmin(newAdmin)
+        .accounts({ stats: statsPDA, admin: admin ? admin.publicKey : user.publicKey })
+        .signers(admin ? [admin] : [])
+        .rpc();
+
+    const acceptAdmin = (newAdmin?: anchor.web3.Keypair) =>
+      program.methods
+        .acceptAdmin()
+        .accounts({ stats: statsPDA, newAdmin: newAdmin ? newAdmin.publicKey : user.publicKey })
+        .signers(newAdmin ? [newAdmin] : [])
+        .rpc();
+
+    it("Hands the admin role over only once the nominee accepts", async () => {
+      const successor = await newUser();
+      await expectError(acceptAdmin(successor), "NoPendingAdmin");
+      await expectError(proposeAdmin(successor.publicKey, successor), "Unauthorized");
+
+      await proposeAdmin(successor.publicKey);
+      let stats = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.admin.toString()).to.equal(user.publicKey.toString());
+      expect(stats.pendingAdmin.toString()).to.equal(successor.publicKey.toString());
+
+      await expectError(acceptAdmin(await newUser()), "Unauthorized");
+
+      await acceptAdmin(successor);
+      stats = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.admin.toString()).to.equal(successor.publicKey.toString());
+      expect(stats.pendingAdmin).to.be.null;
+
+      // Hand the role back so the other admin tests keep working
+      await proposeAdmin(user.publicKey, successor);
+      await acceptAdmin();
+      stats = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.admin.toString()).to.equal(user.publicKey.toString());
+    });
+  });
+
+  describe("featured wish", () => {
+    const setFeatured = (wish: anchor.web3.PublicKey | null, admin?: anchor.web3.Keypair) =>
+      program.methods
+        .setFeatured(wish)
+        .accounts({ stats: statsPDA, admin: admin ? admin.publicKey : user.publicKey })
+        .remainingAccounts(wish ? [{ pubkey: wish, isSigner: false, isWritable: false }] : [])
+        .signers(admin ? [admin] : [])
+        .rpc();
+
+    it("Lets the admin feature a wish and clear it again", async () => {
+      const wish = await submitWish("I wish to be wish of the day");
+
+      await setFeatured(wish.wishPDA);
+      let stats = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.featuredWish.toString()).to.equal(wish.wishPDA.toString());
+      expect(stats.featuredAt.toNumber()).to.be.greaterThan(0);
+
+      await setFeatured(null);
+      stats = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.featuredWish).to.be.null;
+      expect(stats.featuredAt.toNumber()).to.equal(0);
+    });
+
+    it("Rejects featuring from a non-admin or featuring a non-wish account", async () => {
+      const wish = await submitWish("I wish to be featured by anyone");
+      await expectError(setFeatured(wish.wishPDA, await newUser()), "Unauthorized");
+      await expectError(setFeatured(statsPDA), "NotAWish");
+    });
+  });
+
+  describe("comments", () => {
+    const findCommentPDA = async (
+      wishPDA: anchor.web3.PublicKey,
+      author: anchor.web3.PublicKey,
+      index: number
+    ) => {
+      const [commentPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+        [
+          Buffer.from("comment"),
+          wishPDA.toBuffer(),
+          author.toBuffer(),
+          new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      return commentPDA;
+    };
+
+    // Add a comment at the wish's current comment index and return its PDA
+    const addComment = async (wish: SubmittedWish, author: anchor.web3.Keypair, text: string) => {
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      const commentPDA = await findCommentPDA(
+        wish.wishPDA,
+        author.publicKey,
+        wishAccount.commentCount.toNumber()
+      );
+      await program.methods
+        .addComment(wish.wishId, text)
+        .accounts({
+          wish: wish.wishPDA,
+          comment: commentPDA,
+          author: author.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([author])
+        .rpc();
+      return commentPDA;
+    };
+
+    const deleteComment = (
+      wish: SubmittedWish,
+      commentPDA: anchor.web3.PublicKey,
+      author: anchor.web3.PublicKey,
+      authority: anchor.web3.Keypair
+    ) =>
+      program.methods
+        .deleteComment()
+        .accounts({
+          comment: commentPDA,
+          wish: wish.wishPDA,
+          author,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+    it("Increments the comment counter for each comment", async () => {
+      const wish = await submitWish("I wish for encouragement");
+      const author = await newUser();
+
+      const first = await addComment(wish, author, "You can do it!");
+      const second = await addComment(wish, author, "Still rooting for you");
+
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.commentCount.toNumber()).to.equal(2);
+
+      const firstComment = await program.account.comment.fetch(first);
+      const secondComment = await program.account.comment.fetch(second);
+      expect(firstComment.index.toNumber()).to.equal(0);
+      expect(secondComment.index.toNumber()).to.equal(1);
+      expect(secondComment.text).to.equal("Still rooting for you");
+      expect(secondComment.author.toString()).to.equal(author.publicKey.toString());
+    });
+
+    it("Rejects a comment over the maximum length", async () => {
+      const wish = await submitWish("I wish for short comments");
+      await expectError(addComment(wish, await newUser(), "a".repeat(201)), "CommentTooLong");
+    });
+
+    it("Lets the author or the wish owner delete a comment, refunding the author", async () => {
+      const wish = await submitWish("I wish for a tidy comment section");
+      const author = await newUser();
+      const stranger = await newUser();
+
+      const byAuthor = await addComment(wish, author, "First!");
+      const byOwner = await addComment(wish, author, "Second!");
+
+      await expectError(
+        deleteComment(wish, byAuthor, author.publicKey, stranger),
+        "UnauthorizedCommentDeletion"
+      );
+
+      await deleteComment(wish, byAuthor, author.publicKey, author);
+      expect(await provider.connection.getAccountInfo(byAuthor)).to.be.null;
+
+      const rent = await provider.connection.getBalance(byOwner);
+      const authorBefore = await provider.connection.getBalance(author.publicKey);
+      await deleteComment(wish, byOwner, author.publicKey, wish.owner);
+      const authorAfter = await provider.connection.getBalance(author.publicKey);
+      expect(authorAfter - authorBefore).to.equal(rent);
+    });
+  });
+
+  describe("expiry", () => {
+    const reapExpired = async (wish: SubmittedWish) =>
+      program.methods
+        .reapExpired(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+          owner: wish.owner.publicKey,
+        })
+        .rpc();
+
+    it("Only reaps a wish once its expiry has passed, refunding the owner", async () => {
+      const wish = await submitWish("I wish for a short-lived wish", { ttlSecs: 2 });
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.expiresAt.sub(wishAccount.createdAt).toNumber()).to.equal(2);
+
+      await expectError(reapExpired(wish), "NotExpired");
+
+      await sleep(4 * 1000);
+      const rent = await provider.connection.getBalance(wish.wishPDA);
+      const ownerBefore = await provider.connection.getBalance(wish.owner.publicKey);
+
+      // Reaped by the provider wallet, not the owner - the rent still goes to the owner
+      await reapExpired(wish);
+
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      const ownerAfter = await provider.connection.getBalance(wish.owner.publicKey);
+      expect(ownerAfter - ownerBefore).to.equal(rent);
+      const profile = await program.account.userProfile.fetch(await findProfilePDA(wish.owner.publicKey));
+      expect(profile.activeCount).to.equal(0);
+    });
+
+    it("Never reaps a wish without a ttl", async () => {
+      const wish = await submitWish("I wish to stay forever");
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.expiresAt.toNumber()).to.equal(0);
+
+      await expectError(reapExpired(wish), "NotExpired");
+    });
+  });
+
+  describe("moderation", () => {
+    const reportWish = async (wish: SubmittedWish, reporter: anchor.web3.Keypair) => {
+      const [reportPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("report"), wish.wishPDA.toBuffer(), reporter.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .reportWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          report: reportPDA,
+          reporter: reporter.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([reporter])
+        .rpc();
+    };
+
+    const adminRemoveWish = async (wish: SubmittedWish, admin?: anchor.web3.Keypair) =>
+      program.methods
+        .adminRemoveWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          board: wish.board,
+          stats: statsPDA,
+          profile: await findProfilePDA(wish.owner.publicKey),
+          wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+          owner: wish.owner.publicKey,
+          admin: admin ? admin.publicKey : user.publicKey,
+        })
+        .signers(admin ? [admin] : [])
+        .rpc();
+
+    it("Counts each reporter once", async () => {
+      const wish = await submitWish("I wish to be flagged");
+      const reporter = await newUser();
+
+      await reportWish(wish, reporter);
+      await expectError(reportWish(wish, reporter), "AlreadyReported");
+
+      await reportWish(wish, await newUser());
+      const wishAccount = await program.account.aWish.fetch(wish.wishPDA);
+      expect(wishAccount.reports).to.equal(2);
+    });
+
+    it("Only the admin can remove a wish, and the rent goes to the owner", async () => {
+      const wish = await submitWish("I wish to be moderated");
+
+      await expectError(adminRemoveWish(wish, await newUser()), "Unauthorized");
+
+      const rent = await provider.connection.getBalance(wish.wishPDA);
+      const ownerBefore = await provider.connection.getBalance(wish.owner.publicKey);
+      await adminRemoveWish(wish);
+
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      const ownerAfter = await provider.connection.getBalance(wish.owner.publicKey);
+      expect(ownerAfter - ownerBefore).to.equal(rent);
+    });
+
+    describe("reaping a user", () => {
+      const adminReapUser = async (
+        target: anchor.web3.PublicKey,
+        wishPDAs: anchor.web3.PublicKey[],
+        admin?: anchor.web3.Keypair
+      ) =>
+        program.methods
+          .adminReapUser(target)
+          .accounts({
+            board: mainBoardPDA,
+            stats: statsPDA,
+            profile: await findProfilePDA(target),
+            wishIndex: await findWishIndexPDA(target),
+            owner: target,
+            admin: admin ? admin.publicKey : user.publicKey,
+          })
+          .remainingAccounts(wishPDAs.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+          .signers(admin ? [admin] : [])
+          .rpc();
+
+      it("Closes several of a spammer's wishes at once and refunds the spammer", async () => {
+        const spammer = await newUser();
+        const wishes = await submitMany(spammer, 3);
+        const wishPDAs = wishes.map((wish) => wish.wishPDA);
+        let rent = 0;
+        for (const wishPDA of wishPDAs) {
+          rent += await provider.connection.getBalance(wishPDA);
+        }
+        const indexPDA = await findWishIndexPDA(spammer.publicKey);
+        const indexBefore = await provider.connection.getBalance(indexPDA);
+        const statsBefore = await program.account.wallStats.fetch(statsPDA);
+        const spammerBefore = await provider.connection.getBalance(spammer.publicKey);
+
+        await adminReapUser(spammer.publicKey, wishPDAs);
+
+        for (const wishPDA of wishPDAs) {
+          expect(await provider.connection.getAccountInfo(wishPDA)).to.be.null;
+        }
+        // The spammer gets the wishes' rent back, plus whatever the shrunk wish index released
+        const indexAfter = await provider.connection.getBalance(indexPDA);
+        const spammerAfter = await provider.connection.getBalance(spammer.publicKey);
+        expect(spammerAfter - spammerBefore).to.equal(rent + indexBefore - indexAfter);
+
+        const statsAfter = await program.account.wallStats.fetch(statsPDA);
+        expect(statsBefore.active.sub(statsAfter.active).toNumber()).to.equal(3);
+        const profile = await program.account.userProfile.fetch(await findProfilePDA(spammer.publicKey));
+        expect(profile.activeCount).to.equal(0);
+        const index = await program.account.wishIndex.fetch(indexPDA);
+        expect(index.wishes).to.be.empty;
+      });
+
+      it("Aborts the whole batch if any wish belongs to someone else", async () => {
+        const spammer = await newUser();
+        const [mine] = await submitMany(spammer, 1);
+        const bystander = await submitWish("I wish to stay out of this");
+
+        await expectError(
+          adminReapUser(spammer.publicKey, [mine.wishPDA, bystander.wishPDA]),
+          "WishOwnerMismatch"
+        );
+        expect(await provider.connection.getAccountInfo(mine.wishPDA)).to.not.be.null;
+        expect(await provider.connection.getAccountInfo(bystander.wishPDA)).to.not.be.null;
+      });
+
+      it("Only lets the wall admin reap", async () => {
+        const spammer = await newUser();
+        const [wish] = await submitMany(spammer, 1);
+        await expectError(adminReapUser(spammer.publicKey, [wish.wishPDA], await newUser()), "Unauthorized");
+      });
+    });
+  });
+
+  describe("likes", () => {
+    const findLikePDA = async (
+      wishPDA: anchor.web3.PublicKey,
+      voter: anchor.web3.PublicKey
+    ) => {
+      const [likePDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("like"), wishPDA.toBuffer(), voter.toBuffer()],
+        program.programId
+      );
+      return likePDA;
+    };
+
+    const likeWish = async (
+      wishPDA: anchor.web3.PublicKey,
+      voter: anchor.web3.Keypair,
+      likePDA: anchor.web3.PublicKey
+    ) =>
+      program.methods
+        .likeWish()
+        .accounts({
+          like: likePDA,
+          wish: wishPDA,
+          stats: statsPDA,
+          profile: await findProfilePDA(voter.publicKey),
+          voter: voter.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+    it("Counts a like once and rejects a second like from the same voter", async () => {
+      const { wishPDA } = await submitWish("I wish to be liked");
+      const voter = await newUser();
+      const likePDA = await findLikePDA(wishPDA, voter.publicKey);
+
+      await likeWish(wishPDA, voter, likePDA);
+      let wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.likes.toNumber()).to.equal(1);
+
+      await expectError(likeWish(wishPDA, voter, likePDA), "AlreadyLiked");
+
+      wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.likes.toNumber()).to.equal(1);
+    });
+
+    it("Unliking closes the like and refunds its rent to the voter", async () => {
+      const { wishPDA } = await submitWish("I wish to be unliked");
+      const voter = await newUser();
+      const likePDA = await findLikePDA(wishPDA, voter.publicKey);
+
+      await likeWish(wishPDA, voter, likePDA);
+      const likeRent = await provider.connection.getBalance(likePDA);
+      const voterBefore = await provider.connection.getBalance(voter.publicKey);
+
+      // The provider wallet pays the fee, so the voter's balance moves by exactly the refund
+      await program.methods
+        .unlikeWish()
+        .accounts({
+          like: likePDA,
+          wish: wishPDA,
+          voter: voter.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+      const voterAfter = await provider.connection.getBalance(voter.publicKey);
+      expect(voterAfter - voterBefore).to.equal(likeRent);
+      expect(await provider.connection.getAccountInfo(likePDA)).to.be.null;
+
+      const wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.likes.toNumber()).to.equal(0);
+    });
+
+    it("Weights a tipper's like above a fresh wallet's while weighting is on", async () => {
+      const { wishPDA } = await submitWish("I wish for likes that mean something");
+      const fresh = await newUser();
+      const patron = await newUser();
+
+      // 0.25 SOL tipped earns two extra units of weight (one per 0.1 SOL)
+      const tipped = await submitWish("I wish to be tipped by a patron");
+      await program.methods
+        .tipWish(tipped.wishId, new anchor.BN(250_000_000))
+        .accounts({
+          wish: tipped.wishPDA,
+          owner: tipped.owner.publicKey,
+          stats: statsPDA,
+          treasury: treasuryPDA,
+          profile: await findProfilePDA(patron.publicKey),
+          tipper: patron.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([patron])
+        .rpc();
+
+      const setLikeWeighting = (enabled: boolean) =>
+        program.methods.setLikeWeighting(enabled).accounts({ stats: statsPDA, admin: user.publicKey }).rpc();
+
+      await setLikeWeighting(true);
+      try {
+        await likeWish(wishPDA, fresh, await findLikePDA(wishPDA, fresh.publicKey));
+        await likeWish(wishPDA, patron, await findLikePDA(wishPDA, patron.publicKey));
+      } finally {
+        await setLikeWeighting(false);
+      }
+
+      const freshLike = await program.account.wishLike.fetch(await findLikePDA(wishPDA, fresh.publicKey));
+      const patronLike = await program.account.wishLike.fetch(await findLikePDA(wishPDA, patron.publicKey));
+      expect(freshLike.weight.toNumber()).to.equal(1);
+      expect(patronLike.weight.toNumber()).to.equal(3);
+
+      let wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.likes.toNumber()).to.equal(2);
+      expect(wishAccount.weightedLikes.toNumber()).to.equal(4);
+
+      // Unliking takes back the weight the like was cast with, even though weighting is off now
+      await program.methods
+        .unlikeWish()
+        .accounts({
+          like: await findLikePDA(wishPDA, patron.publicKey),
+          wish: wishPDA,
+          voter: patron.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([patron])
+        .rpc();
+
+      wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.likes.toNumber()).to.equal(1);
+      expect(wishAccount.weightedLikes.toNumber()).to.equal(1);
+    });
+
+    it("Gives no extra weight for trying to tip your own wish", async () => {
+      const { wishPDA } = await submitWish("I wish for honest weights");
+      const own = await submitWish("I wish to tip myself rich");
+      const profilePDA = await findProfilePDA(own.owner.publicKey);
+
+      await expectError(
+        program.methods
+          .tipWish(own.wishId, new anchor.BN(500_000_000))
+          .accounts({
+            wish: own.wishPDA,
+            owner: own.owner.publicKey,
+            stats: statsPDA,
+            treasury: treasuryPDA,
+            profile: profilePDA,
+            tipper: own.owner.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([own.owner])
+          .rpc(),
+        "SelfTip"
+      );
+      expect((await program.account.userProfile.fetch(profilePDA)).tipsGiven.toNumber()).to.equal(0);
+
+      await program.methods.setLikeWeighting(true).accounts({ stats: statsPDA, admin: user.publicKey }).rpc();
+      try {
+        await likeWish(wishPDA, own.owner, await findLikePDA(wishPDA, own.owner.publicKey));
+      } finally {
+        await program.methods.setLikeWeighting(false).accounts({ stats: statsPDA, admin: user.publicKey }).rpc();
+      }
+      const like = await program.account.wishLike.fetch(await findLikePDA(wishPDA, own.owner.publicKey));
+      expect(like.weight.toNumber()).to.equal(1);
+    });
+
+    it("Counts every like as 1 while weighting is off", async () => {
+      const { wishPDA } = await submitWish("I wish for plain likes");
+      const voter = await newUser();
+      await likeWish(wishPDA, voter, await findLikePDA(wishPDA, voter.publicKey));
+
+      const wishAccount = await program.account.aWish.fetch(wishPDA);
+      expect(wishAccount.weightedLikes.toNumber()).to.equal(wishAccount.likes.toNumber());
+    });
+  });
+
+  describe("follows", () => {
+    const findFollowPDA = async (
+      wishPDA: anchor.web3.PublicKey,
+      follower: anchor.web3.PublicKey
+    ) => {
+      const [followPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("follow"), wishPDA.toBuffer(), follower.toBuffer()],
+        program.programId
+      );
+      return followPDA;
+    };
+
+    const follow = async (wish: SubmittedWish, follower: anchor.web3.Keypair) =>
+      program.methods
+        .followWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          follow: await findFollowPDA(wish.wishPDA, follower.publicKey),
+          follower: follower.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([follower])
+        .rpc();
+
+    const unfollow = async (wish: SubmittedWish, follower: anchor.web3.Keypair) =>
+      program.methods
+        .unfollowWish(wish.wishId)
+        .accounts({
+          wish: wish.wishPDA,
+          follow: await findFollowPDA(wish.wishPDA, follower.publicKey),
+          follower: follower.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([follower])
+        .rpc();
+
+    const followers = async (wish: SubmittedWish) =>
+      (await program.account.aWish.fetch(wish.wishPDA)).followers;
+
+    it("Rejects following the same wish twice", async () => {
+      const wish = await submitWish("I wish to be watched");
+      const follower = await newUser();
+
+      await follow(wish, follower);
+      await expectError(follow(wish, follower), "AlreadyFollowing");
+      expect(await followers(wish)).to.equal(1);
+    });
+
+    it("Keeps the follower count consistent through follow/unfollow cycles", async () => {
+      const wish = await submitWish("I wish for a loyal audience");
+      const alice = await newUser();
+      const bob = await newUser();
+
+      await follow(wish, alice);
+      await follow(wish, bob);
+      expect(await followers(wish)).to.equal(2);
+
+      await unfollow(wish, alice);
+      expect(await followers(wish)).to.equal(1);
+
+      // Following again after unfollowing re-creates the closed follow PDA
+      await follow(wish, alice);
+      expect(await followers(wish)).to.equal(2);
+
+      await unfollow(wish, alice);
+      await unfollow(wish, bob);
+      expect(await followers(wish)).to.equal(0);
+    });
+  });
+
+  describe("reactions", () => {
+    const findReactionPDA = async (
+      wishPDA: anchor.web3.PublicKey,
+      reactor: anchor.web3.PublicKey
+    ) => {
+      const [reactionPDA, _] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("reaction"), wishPDA.toBuffer(), reactor.toBuffer()],
+        program.programId
+      );
+      return reactionPDA;
+    };
+
+    const react = async (wish: SubmittedWish, reactor: anchor.web3.Keypair, reaction: object) =>
+      program.methods
+        .reactWish(wish.wishId, reaction)
+        .accounts({
+          wish: wish.wishPDA,
+          reaction: await findReactionPDA(wish.wishPDA, reactor.publicKey),
+          reactor: reactor.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([reactor])
+        .rpc();
+
+    // Counts are indexed Heart, Star, Clap, Fire
+    const reactions = async (wish: SubmittedWish) =>
+      (await program.account.aWish.fetch(wish.wishPDA)).reactions;
+
+    it("Switching from Heart to Fire moves the count without paying rent again", async () => {
+      const wish = await submitWish("I wish to light up the room");
+      const alice = await newUser();
+      const bob = await newUser();
+
+      await react(wish, alice, { heart: {} });
+      await react(wish, bob, { heart: {} });
+      expect(await reactions(wish)).to.deep.equal([2, 0, 0, 0]);
+
+      const before = await provider.connection.getBalance(alice.publicKey);
+      await react(wish, alice, { fire: {} });
+      const after = await provider.connection.getBalance(alice.publicKey);
+
+      expect(await reactions(wish)).to.deep.equal([1, 0, 0, 1]);
+      expect(after).to.equal(before);
+
+      const record = await program.account.wishReaction.fetch(
+        await findReactionPDA(wish.wishPDA, alice.publicKey)
+      );
+      expect(record.reaction).to.deep.equal({ fire: {} });
+    });
+
+    it("Doesn't stack repeated reactions from the same user", async () => {
+      const wish = await submitWish("I wish for a standing ovation");
+      const fan = await newUser();
+
+      await react(wish, fan, { clap: {} });
+      await react(wish, fan, { clap: {} });
+      expect(await reactions(wish)).to.deep.equal([0, 0, 1, 0]);
+    });
+  });
+
+  describe("leaderboard", () => {
+    it("Ranks the most active wishers in order", async () => {
+      const [low, high, middle] = [await newUser(), await newUser(), await newUser()];
+      await submitMany(low, 6);
+      await submitMany(high, 8);
+      await submitMany(middle, 7);
+
+      const { entries } = await program.account.leaderboard.fetch(leaderboardPDA);
+      const counts = entries.map((entry) => entry.wishCount);
+      expect(counts).to.deep.equal([...counts].sort((a, b) => b - a));
+
+      const rank = (wisher: anchor.web3.Keypair) =>
+        entries.findIndex((entry) => entry.user.toString() === wisher.publicKey.toString());
+      expect(rank(high)).to.be.at.least(0);
+      expect(rank(high)).to.be.lessThan(rank(middle));
+      expect(rank(middle)).to.be.lessThan(rank(low));
+      expect(entries[rank(high)].wishCount).to.equal(8);
+      expect(entries[rank(middle)].wishCount).to.equal(7);
+      expect(entries[rank(low)].wishCount).to.equal(6);
+    });
+  });
+
+  describe("bulk delete", () => {
+    // Each wish goes in remaining_accounts followed by its escrow PDA
+    const deleteAll = async (owner: anchor.web3.Keypair, wishPDAs: anchor.web3.PublicKey[]) =>
+      program.methods
+        .deleteAll()
+        .accounts({
+          board: mainBoardPDA,
+          stats: statsPDA,
+          profile: await findProfilePDA(owner.publicKey),
+          wishIndex: await findWishIndexPDA(owner.publicKey),
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          (await Promise.all(wishPDAs.map(async (wishPDA) => [wishPDA, await findEscrowPDA(wishPDA)]))).flatMap(
+            ([wishPDA, escrowPDA]) => [
+              { pubkey: wishPDA, isSigner: false, isWritable: true },
+              { pubkey: escrowPDA, isSigner: false, isWritable: false },
+            ]
+          )
+        )
+        .signers([owner])
+        .rpc();
+
+    it("Rejects more than MAX_DELETE_BATCH (10) wishes before loading any of them", async () => {
+      const owner = await newUser();
+      const [wish] = await submitMany(owner, 1);
+
+      await expectError(deleteAll(owner, new Array(11).fill(wish.wishPDA)), "BatchTooLarge");
+      expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.not.be.null;
+    });
+
+    it("Deletes three wishes in one transaction and refunds their rent", async () => {
+      const owner = await newUser();
+      const wishes = await submitMany(owner, 3);
+      const stats = await program.account.wallStats.fetch(statsPDA);
+      let rent = 0;
+      for (const wish of wishes) {
+        rent += await provider.connection.getBalance(wish.wishPDA);
+      }
+
+      const before = await provider.connection.getBalance(owner.publicKey);
+      await deleteAll(owner, wishes.map((wish) => wish.wishPDA));
+      const after = await provider.connection.getBalance(owner.publicKey);
+
+      expect(after - before).to.equal(rent);
+      for (const wish of wishes) {
+        expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+      }
+      const statsAfter = await program.account.wallStats.fetch(statsPDA);
+      expect(stats.active.sub(statsAfter.active).toNumber()).to.equal(3);
+    });
+
+    it("Fails the whole batch if one wish belongs to someone else", async () => {
+      const owner = await newUser();
+      const mine = await submitMany(owner, 2);
+      const theirs = await submitWish("I wish to keep my wish");
+
+      await expectError(
+        deleteAll(owner, [mine[0].wishPDA, theirs.wishPDA, mine[1].wishPDA]),
+        "UnauthorizedDeletion"
+      );
+      for (const wish of [...mine, theirs]) {
+        expect(await program.account.aWish.fetchNullable(wish.wishPDA)).to.not.be.null;
+      }
+    });
+  });
+
+  describe("activity log", () => {
+    const SUBMIT = 0;
+    const DELETE = 1;
+    const LIKE = 2;
+
+    it("Keeps the latest actions in order once the ring buffer wraps", async () => {
+      const owner = await newUser();
+      const mine = await submitMany(owner, 3);
+      const others = [];
+      for (let i = 0; i < 5; i++) {
+        others.push(await submitWish(`I wish to be liked ${i}`));
+      }
+      for (const wish of others) {
+        await program.methods
+          .likeWish()
+          .accounts({
+            like: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("like"), wish.wishPDA.toBuffer(), owner.publicKey.toBuffer()],
+              program.programId
+            )[0],
+            wish: wish.wishPDA,
+            stats: statsPDA,
+            profile: await findProfilePDA(owner.publicKey),
+            voter: owner.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([owner])
+          .rpc();
+      }
+      await program.methods
+        .deleteAll()
+        .accounts({
+          board: mainBoardPDA,
+          stats: statsPDA,
+          profile: await findProfilePDA(owner.publicKey),
+          wishIndex: await findWishIndexPDA(owner.publicKey),
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          (await Promise.all([mine[0], mine[1]].map(async (wish) => [wish.wishPDA, await findEscrowPDA(wish.wishPDA)]))).flatMap(
+            ([wishPDA, escrowPDA]) => [
+              { pubkey: wishPDA, isSigner: false, isWritable: true },
+              { pubkey: escrowPDA, isSigner: false, isWritable: false },
+            ]
+          )
+        )
+        .signers([owner])
+        .rpc();
+
+      // 10 actions into a log of 8: the first two submissions have been overwritten
+      const profile = await program.account.userProfile.fetch(await findProfilePDA(owner.publicKey));
+      const total = profile.activityTotal.toNumber();
+      expect(total).to.equal(10);
+      const size = profile.activity.length;
+      expect(size).to.equal(8);
+
+      // Read from the oldest slot (total % size) forwards
+      const ordered = [];
+      for (let i = 0; i < size; i++) {
+        ordered.push(profile.activity[(total + i) % size]);
+      }
+      const expected = [
+        { kind: SUBMIT, target: mine[2].wishPDA },
+        ...others.map((wish) => ({ kind: LIKE, target: wish.wishPDA })),
+        { kind: DELETE, target: mine[0].wishPDA },
+        { kind: DELETE, target: mine[1].wishPDA },
+      ];
+      expect(ordered.map((entry) => [entry.kind, entry.target.toBase58()])).to.deep.equal(
+        expected.map((entry) => [entry.kind, entry.target.toBase58()])
+      );
+      for (let i = 1; i < size; i++) {
+        expect(ordered[i].timestamp.toNumber()).to.be.at.least(ordered[i - 1].timestamp.toNumber());
+      }
+    });
+  });
+
+  describe("boards", () => {
+    const fetchBoard = (board: anchor.web3.PublicKey) => program.account.board.fetch(board);
+
+    it("Keeps counters per board", async () => {
+      const board = await createBoard("counters");
+      const main = await fetchBoard(mainBoardPDA);
+      const stats = await program.account.wallStats.fetch(statsPDA);
+
+      const wish = await submitWish("I wish to be counted on my own board", { board });
+      let mine = await fetchBoard(board);
+      expect(mine.totalSubmitted.toNumber()).to.equal(1);
+      expect(mine.active.toNumber()).to.equal(1);
+      expect((await fetchBoard(mainBoardPDA)).totalSubmitted.eq(main.totalSubmitted)).to.be.true;
+
+      // The wall still counts every board together
+      const after = await program.account.wallStats.fetch(statsPDA);
+      expect(after.totalSubmitted.toNumber()).to.equal(stats.totalSubmitted.toNumber() + 1);
+
+      await deleteWish(wish);
+      mine = await fetchBoard(board);
+      expect(mine.totalSubmitted.toNumber()).to.equal(1);
+      expect(mine.active.toNumber()).to.equal(0);
+      expect((await fetchBoard(mainBoardPDA)).active.eq(main.active)).to.be.true;
+    });
+
+    it("Rejects a wish passed with another board", async () => {
+      const wish = await submitWish("I wish to stay on the main board");
+      const other = await createBoard("elsewhere");
+      await expectError(deleteWish({ ...wish, board: other }), "BoardMismatch");
+      await deleteWish(wish);
+    });
+
+    it("Lets a board's admin moderate only their own board", async () => {
+      const admin = await newUser();
+      const board = await createBoard("moderated", admin);
+      expect((await fetchBoard(board)).admin.toBase58()).to.equal(admin.publicKey.toBase58());
+
+      const adminRemoveWish = async (wish: SubmittedWish) =>
+        program.methods
+          .adminRemoveWish(wish.wishId)
+          .accounts({
+            wish: wish.wishPDA,
+            board: wish.board,
+            stats: statsPDA,
+            profile: await findProfilePDA(wish.owner.publicKey),
+            wishIndex: await findWishIndexPDA(wish.owner.publicKey),
+            owner: wish.owner.publicKey,
+            admin: admin.publicKey,
+          })
+          .signers([admin])
+          .rpc();
+
+      const elsewhere = await submitWish("I wish to be out of their reach");
+      await expectError(adminRemoveWish(elsewhere), "Unauthorized");
+
+      const wish = await submitWish("I wish to break the board's rules", { board });
+      await adminRemoveWish(wish);
+      expect(await provider.connection.getAccountInfo(wish.wishPDA)).to.be.null;
+    });
+
+    it("Rejects blank and oversized board names", async () => {
+      await expectError(createBoard(" "), "BoardNameEmpty");
+      await expectError(createBoard("a".repeat(65)), "BoardNameTooLong");
+    });
+  });
+
+  describe("commit-reveal", () => {
+    // The commitment is SHA-256 of the title bytes followed by the 32-byte nonce
+    const commitmentFor = (title: string, nonce: Buffer) =>
+      Array.from(createHash("sha256").update(Buffer.concat([Buffer.from(title), nonce])).digest());
+
+    const commitWish = async (owner: anchor.web3.Keypair, wishId: anchor.BN, commitment: number[]) => {
+      const wishPDA = await findWishPDA(owner.publicKey, wishId);
+      await program.methods
+        .commitWish(wishId, commitment)
+        .accounts({
+          wish: wishPDA,
+          board: mainBoardPDA,
+          stats: statsPDA,
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      return wishPDA;
+    };
+
+    const revealWish = async (owner: anchor.web3.Keypair, wishId: anchor.BN, title: string, nonce: Buffer) =>
+      program.methods
+        .revealWish(wishId, title, Array.from(nonce))
+        .accounts({
+          wish: await findWishPDA(owner.publicKey, wishId),
+          board: mainBoardPDA,
+          stats: statsPDA,
+          leaderboard: leaderboardPDA,
+          treasury: treasuryPDA,
+          profile: await findProfilePDA(owner.publicKey),
+          wishIndex: await findWishIndexPDA(owner.publicKey),
+          user: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+    it("Stores only the hash until a matching reveal grows it into a wish", async () => {
+      const owner = await newUser();
+      const wishId = newWishId();
+      const title = "I wish to win the contest";
+      const nonce = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const wishPDA = await commitWish(owner, wishId, commitmentFor(title, nonce));
+
+      // Just the commitment, and not yet a wish anyone can like
+      expect((await provider.connection.getAccountInfo(wishPDA)).data.length).to.equal(8 + 32 + 32 + 8 + 32 + 8);
+      const committed = await program.account.wishCommitment.fetch(wishPDA);
+      expect(Buffer.from(committed.commitment).equals(Buffer.from(commitmentFor(title, nonce)))).to.be.true;
+      const voter = await newUser();
+      await expectError(
+        program.methods
+          .likeWish()
+          .accounts({
+            like: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("like"), wishPDA.toBuffer(), voter.publicKey.toBuffer()],
+              program.programId
+            )[0],
+            wish: wishPDA,
+            stats: statsPDA,
+            profile: await findProfilePDA(voter.publicKey),
+            voter: voter.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([voter])
+          .rpc(),
+        "AccountDiscriminatorMismatch"
+      );
+
+      await revealWish(owner, wishId, title, nonce);
+      const wish = await program.account.aWish.fetch(wishPDA);
+      expect(wish.title).to.equal(title);
+      expect(wish.user.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(wish.wishId.eq(wishId)).to.be.true;
+      const info = await provider.connection.getAccountInfo(wishPDA);
+      expect(info.data.length).to.equal(wishSpace(title));
+      expect(info.lamports).to.equal(await provider.connection.getMinimumBalanceForRentExemption(wishSpace(title)));
+
+      // Revealed once and for all
+      await expectError(revealWish(owner, wishId, title, nonce), "NotCommitted");
+    });
+
+    it("Rejects a reveal whose title or nonce doesn't match the commitment", async () => {
+      const owner = await newUser();
+      const wishId = newWishId();
+      const title = "I wish to win the contest";
+      const nonce = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const wishPDA = await commitWish(owner, wishId, commitmentFor(title, nonce));
+
+      await expectError(revealWish(owner, wishId, "I wish to win the contest!", nonce), "CommitmentMismatch");
+      await expectError(revealWish(owner, wishId, title, Buffer.alloc(32)), "CommitmentMismatch");
+      expect(await program.account.wishCommitment.fetch(wishPDA)).to.not.be.null;
+    });
+
+    it("Lets the committer withdraw an unrevealed commitment and reuse its wish id", async () => {
+      const owner = await newUser();
+      const wishId = newWishId();
+      const nonce = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const wishPDA = await commitWish(owner, wishId, commitmentFor("I wish to enter the contest", nonce));
+      const cancelCommit = (signer: anchor.web3.Keypair) =>
+        program.methods
+          .cancelCommit(wishId)
+          .accounts({ wish: wishPDA, user: signer.publicKey })
+          .signers([signer])
+          .rpc();
+
+      await expectError(cancelCommit(await newUser()), "ConstraintSeeds");
+
+      const rent = await provider.connection.getBalance(wishPDA);
+      const before = await provider.connection.getBalance(owner.publicKey);
+      await cancelCommit(owner);
+      const after = await provider.connection.getBalance(owner.publicKey);
+      expect(after - before).to.equal(rent);
+      expect(await provider.connection.getAccountInfo(wishPDA)).to.be.null;
+      await expectError(revealWish(owner, wishId, "I wish to enter the contest", nonce), "NotCommitted");
+
+      // The id is free again; once revealed, the wish can't be cancelled like a commitment
+      const title = "I wish to enter the contest after all";
+      await commitWish(owner, wishId, commitmentFor(title, nonce));
+      await revealWish(owner, wishId, title, nonce);
+      await expectError(cancelCommit(owner), "AccountDiscriminatorMismatch");
+    });
+  });
+
+  describe("wish index", () => {
+    const fetchIndex = async (owner: anchor.web3.PublicKey) =>
+      program.account.wishIndex.fetch(await findWishIndexPDA(owner));
+    const keys = (list: anchor.web3.PublicKey[]) => list.map((key) => key.toBase58());
+
+    it("Stays consistent across submits and deletes, resizing in chunks", async () => {
+      const owner = await newUser();
+      const wishes = await submitMany(owner, 6);
+      const [a, b, c, d, e, f] = wishes.map((wish) => wish.wishPDA);
+      const indexPDA = await findWishIndexPDA(owner.publicKey);
+
+      // Grown from one chunk of 4 to two to fit six wishes
+      expect(keys((await fetchIndex(owner.publicKey)).wishes)).to.deep.equal(keys([a, b, c, d, e, f]));
+      expect((await provider.connection.getAccountInfo(indexPDA)).data.length).to.equal(wishIndexSpace(8));
+
+      // Each delete swaps the last entry into the freed slot
+      await deleteWish(wishes[0]);
+      expect(keys((await fetchIndex(owner.publicKey)).wishes)).to.deep.equal(keys([f, b, c, d, e]));
+      await deleteWish(wishes[1]);
+      expect(keys((await fetchIndex(owner.publicKey)).wishes)).to.deep.equal(keys([f, e, c, d]));
+
+      // A transferred wish moves to the new owner's index
+      const newOwner = await newUser();
+      await program.methods
+        .transferWish(wishes[2].wishId, newOwner.publicKey)
+        .accounts({
+          wish: c,
+          stats: statsPDA,
+          fromProfile: await findProfilePDA(owner.publicKey),
+          toProfile: await findProfilePDA(newOwner.publicKey),
+          fromIndex: indexPDA,
+          toIndex: await findWishIndexPDA(newOwner.publicKey),
+          currentOwner: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      expect(keys((await fetchIndex(owner.publicKey)).wishes)).to.deep.equal(keys([f, e, d]));
+      expect(keys((await fetchIndex(newOwner.publicKey)).wishes)).to.deep.equal(keys([c]));
+
+      // Still two chunks: with 3 of 8 slots used there's less than two chunks free
+      expect((await provider.connection.getAccountInfo(indexPDA)).data.length).to.equal(wishIndexSpace(8));
+
+      // Emptying it leaves two free chunks, so it shrinks back to one and refunds the rent
+      const before = await provider.connection.getBalance(indexPDA);
+      for (const wish of [wishes[3], wishes[4], wishes[5]]) {
+        await deleteWish(wish);
+      }
+      const index = await fetchIndex(owner.publicKey);
+      expect(index.wishes).to.be.empty;
+      expect(index.user.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect((await provider.connection.getAccountInfo(indexPDA)).data.length).to.equal(wishIndexSpace(4));
+      expect(await provider.connection.getBalance(indexPDA)).to.be.lessThan(before);
+    });
   });
-}); 
\ No newline at end of file
+});